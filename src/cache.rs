@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Root of everything the tool caches, e.g. `~/.cache/github-stats` on Linux
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("github-stats"))
+}
+
+/// A response body kept alongside the `ETag` it was served with
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub etag: String,
+    pub body: String,
+}
+
+/// Stored responses unused for this long are deleted whenever a new one is written
+const RESPONSE_MAX_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// 64-bit FNV-1a. Unlike std's `DefaultHasher` it's fixed, so file names survive toolchain updates.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn response_path(url: &str) -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("etags").join(format!("{:016x}.json", stable_hash(url))))
+}
+
+/// Delete the files in `dir` last written more than `max_age` ago; best-effort, like all caching
+fn prune(dir: &Path, max_age: Duration) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let stale = entry.metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > max_age);
+        if stale {
+            std::fs::remove_file(entry.path()).ok();
+        }
+    }
+}
+
+/// Previously stored response for `url`, if any
+pub fn load_response(url: &str) -> Option<CachedResponse> {
+    let contents = std::fs::read_to_string(response_path(url)?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Remember `body` and its `etag` so the next request for `url` can be conditional
+pub fn store_response(url: &str, etag: &str, body: &str) -> std::io::Result<()> {
+    let path = response_path(url).ok_or_else(|| std::io::Error::other("no cache directory"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+        prune(parent, RESPONSE_MAX_AGE);
+    }
+    let entry = CachedResponse {
+        etag: etag.to_string(),
+        body: body.to_string(),
+    };
+    std::fs::write(path, serde_json::to_string(&entry)?)
+}
+
+/// Stats saved by an earlier run, with when they were fetched
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedStats<T> {
+    /// Seconds since the Unix epoch
+    fetched_at: u64,
+    stats: T,
+}
+
+fn stats_path(key: &str) -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("stats").join(format!("{:016x}.json", stable_hash(key))))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Stats stored under `key` that are at most `max_age` old
+pub fn load_stats<T: DeserializeOwned>(key: &str, max_age: Duration) -> Option<T> {
+    let contents = std::fs::read_to_string(stats_path(key)?).ok()?;
+    let cached: CachedStats<T> = serde_json::from_str(&contents).ok()?;
+    (now_secs().saturating_sub(cached.fetched_at) <= max_age.as_secs()).then_some(cached.stats)
+}
+
+/// Save freshly fetched stats under `key`
+pub fn store_stats<T: Serialize>(key: &str, stats: &T) -> std::io::Result<()> {
+    let path = stats_path(key).ok_or_else(|| std::io::Error::other("no cache directory"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let entry = CachedStats {
+        fetched_at: now_secs(),
+        stats,
+    };
+    std::fs::write(path, serde_json::to_string(&entry)?)
+}
@@ -1,9 +1,14 @@
-use std::io::{stdout, Write};
+mod cache;
+
+use std::collections::{HashMap, HashSet};
+use std::io::{stdout, IsTerminal, Write};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::{Result, Context};
-use chrono::{Utc, Datelike, NaiveDate, TimeZone};
-use clap::Parser;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use clap::{Parser, Subcommand};
 use colored::*;
 use crossterm::{
     event::{self, Event as CrosstermEvent, KeyCode, KeyEventKind, KeyModifiers},
@@ -12,30 +17,344 @@ use crossterm::{
 use serde::{Deserialize, Serialize};
 
 // Constants
-const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 const WEEKS_IN_YEAR: usize = 52;
-const DAY_LABELS: &[&str] = &["   Mon", "      ", "   Wed", "      ", "   Fri", "      "];
-const MONTH_SPACING: usize = 10;
+const DAY_LABEL_WIDTH: usize = 6;
+const CELL_WIDTH: usize = 3; // leading space plus a double-width square
+const TIGHT_CELL_WIDTH: usize = 2;
+const SCROLL_STEP_WEEKS: usize = 4;
+const MAX_CELL_COUNT: u32 = 99; // largest count that fits in a cell, anything above shows as "+"
+/// GitHub's `contributionLevel` values, indexed by our 0-4 level
+const CONTRIBUTION_LEVELS: [&str; 5] = ["NONE", "FIRST_QUARTILE", "SECOND_QUARTILE", "THIRD_QUARTILE", "FOURTH_QUARTILE"];
+const DEFAULT_QUANTILES: [f64; 3] = [0.25, 0.5, 0.75];
+const EVENTS_MAX_PAGES: u32 = 3; // the events API stops at 300 events
+const EVENTS_HISTORY_DAYS: i64 = 90;
+const LEADERBOARD_CONCURRENCY: usize = 4;
+const REPO_SCAN_CONCURRENCY: usize = 4;
+const ORG_MEMBERS_LIMIT: usize = 200; // every member costs a few requests, so big orgs are cut off
+const DEFAULT_SCAN_REPOS: usize = 5;
+const MAX_REPOS: usize = 100; // GraphQL's page size limit
+const REPO_COLUMN_WIDTH: usize = 35; // narrowest the repository column gets, longer names widen it
+const DEFAULT_ACTIVE_THRESHOLD: u32 = 1;
+const DEFAULT_API_URL: &str = "https://api.github.com";
+const DEFAULT_CACHE_TTL_MINUTES: u64 = 60;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_MAX_PAGES: u32 = 10;
+const MIN_WATCH_SECS: u64 = 30; // every refresh costs a handful of requests against the hourly limit
+const PEEK_CACHE_TTL: Duration = Duration::from_secs(60); // prompts redraw constantly, but today's count moves
+const PINNED_DESCRIPTION_WIDTH: usize = 50;
+const LIST_COMMITS_LIMIT: usize = 10;
+const COMMIT_MESSAGE_WIDTH: usize = 60;
+const MIN_PROJECTION_DAYS: u32 = 14; // projecting from the first days of January swings wildly
+const RATE_LIMIT_PACING_THRESHOLD: u32 = 20;
+const MAX_RATE_LIMIT_SLEEP: Duration = Duration::from_secs(120); // longer resets end the run with a RateLimited error
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60); // when neither Retry-After nor a reset time is sent
+const TRANSIENT_RETRY_ATTEMPTS: u32 = 3;
+const TRANSIENT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const FILTERED_REPO_FETCH: usize = 100; // GraphQL's page size limit, fetched when --repo-filter or --lang narrows the list
+const MAX_WINDOW_DAYS: u32 = 365; // contributionsCollection rejects spans over a year
+/// GraphQL comment in the calendar query that `--graphql-extra` fields replace
+const GRAPHQL_EXTRA_MARKER: &str = "# --graphql-extra fields";
+const DEFAULT_GRAPHQL_EXTRA_OUTPUT: &str = "graphql-extra.json";
+const MIN_PLAUSIBLE_WEEKS: usize = 50; // a full calendar has 53, anything much shorter is a glitch
 
 #[derive(Parser)]
 #[command(name = "github-stats")]
 #[command(about = "Display GitHub contribution statistics")]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// GitHub username to analyze
     username: Option<String>,
     
-    /// GitHub personal access token
-    #[arg(short, long, env)]
-    token: Option<String>,
+    /// GitHub personal access token; repeat it (or separate tokens with commas) to switch to the next one when a token runs out of rate limit
+    #[arg(short, long, env, value_delimiter = ',')]
+    token: Vec<String>,
+
+    /// Time zone that days, weeks and commit windows follow, as an IANA name like Europe/Berlin, or UTC [default: the system's]
+    #[arg(long, value_name = "ZONE")]
+    timezone: Option<String>,
+
+    /// Never run 'gh auth token' for a missing token; only --token, TOKEN and GITHUB_TOKEN are used
+    #[arg(long)]
+    no_gh_fallback: bool,
     
-    /// Output format (text, json)
-    #[arg(short, long, default_value = "text")]
-    format: String,
+    /// Output format (text, ansi-text, json, github-json, svg, html, calendar, total, badge, punchcard, table, csv, weekly-csv, markdown) [default: text, or from the --output extension]
+    #[arg(short, long)]
+    format: Option<String>,
+
+    /// Write the output to this file instead of stdout
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<std::path::PathBuf>,
+
+    /// Color theme for rendered output (dark, light)
+    #[arg(long, default_value = "dark")]
+    theme: String,
+
+    /// Load the theme from a JSON file with the five level colors, legend labels and optional symbols
+    #[arg(long, value_name = "PATH")]
+    theme_file: Option<std::path::PathBuf>,
+
+    /// Emit an SVG that follows the viewer's light/dark preference instead of a fixed theme
+    #[arg(long)]
+    svg_responsive: bool,
+
+    /// Stop counting a repository's commits after this many pages of 100
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_PAGES, value_parser = clap::value_parser!(u32).range(1..))]
+    max_pages: u32,
+
+    /// Abort once this many API requests have been made in a single run
+    #[arg(long)]
+    max_requests: Option<u32>,
+
+    /// REST API base URL, for GitHub Enterprise Server (e.g. https://github.example.com/api/v3)
+    #[arg(long, env = "GITHUB_API_URL", default_value = DEFAULT_API_URL)]
+    api_url: String,
+
+    /// GraphQL endpoint [default: the one that goes with --api-url]
+    #[arg(long, env = "GITHUB_GRAPHQL_URL")]
+    graphql_url: Option<String>,
+
+    /// GitHub Enterprise Server host (e.g. github.example.com); takes precedence over --api-url
+    #[arg(long, env = "GH_HOST", value_name = "HOST")]
+    base_url: Option<String>,
+
+    /// Give up on a request (connecting included) after this many seconds
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_TIMEOUT_SECS, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: u64,
+
+    /// Stop retrying once the run has spent this long waiting on retries and rate limits (e.g. 60s, 2m)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    max_total_retry_time: Option<Duration>,
+
+    /// Number of recently pushed repositories to list (at most 100)
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(0..=MAX_REPOS as i64))]
+    repos: u32,
+
+    /// How many of the listed repositories get their commits counted [default: 5, at most --repos]
+    #[arg(long)]
+    scan_repos: Option<usize>,
+
+    /// Leave private contributions out of the calendar when querying yourself
+    #[arg(long, overrides_with = "include_private")]
+    public_only: bool,
+
+    /// Keep private contributions in the calendar when querying yourself (the default; undoes --public-only)
+    #[arg(long, overrides_with = "public_only")]
+    include_private: bool,
+
+    /// List the user's most starred repositories instead of recent activity [default count: 10]
+    #[arg(long, value_name = "COUNT", num_args = 0..=1, default_missing_value = "10", conflicts_with = "output")]
+    top_repos: Option<usize>,
+
+    /// Compare two saved JSON snapshots (from --format json) instead of fetching
+    #[arg(long, num_args = 2, value_names = ["OLD", "NEW"])]
+    diff: Option<Vec<std::path::PathBuf>>,
+
+    /// Draw one head-to-head grid against this user: each day is colored by whether USERNAME or USER contributed more
+    #[arg(long, value_name = "USER", conflicts_with_all = ["format", "output", "leaderboard", "org_members"])]
+    diff_graph: Option<String>,
+
+    /// Build the graph from the user's commits to a single repository (owner/name)
+    #[arg(long)]
+    repo: Option<String>,
+
+    /// Add another account of yours to the graph, summed by date; pair each with an --also-token (repeatable)
+    #[arg(long, value_name = "NAME", requires = "also_token", conflicts_with = "repo")]
+    also_user: Vec<String>,
+
+    /// Token for the --also-user at the same position
+    #[arg(long, value_name = "TOKEN", requires = "also_user")]
+    also_token: Vec<String>,
+
+    /// Where the graph's counts come from: GitHub's contribution calendar, or the user's commits counted locally
+    #[arg(long, value_enum, default_value_t = GraphSource::Calendar)]
+    source: GraphSource,
+
+    /// Quantile boundaries between levels 1-4 for locally computed graphs
+    #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_QUANTILES)]
+    quantiles: Vec<f64>,
+
+    /// Print each day's contribution count instead of a colored square
+    #[arg(long)]
+    show_counts: bool,
+
+    /// Draw with plain ASCII instead of emoji and box-drawing characters, for logs and limited terminals
+    #[arg(long)]
+    ascii: bool,
+
+    /// Also write the period summary (today/week/month/year, streaks) as JSON to this file
+    #[arg(long, value_name = "PATH")]
+    summary_json: Option<std::path::PathBuf>,
+
+    /// List the available themes with a preview of their colors and exit
+    #[arg(long)]
+    list_themes: bool,
+
+    /// Recompute levels from the counts relative to the busiest day instead of GitHub's quartiles
+    #[arg(long)]
+    normalize: bool,
+
+    /// File with one GitHub username per line (blank lines and # comments are ignored)
+    #[arg(long, value_name = "PATH", requires = "leaderboard")]
+    users_file: Option<std::path::PathBuf>,
+
+    /// Rank the users from --users-file by total contributions
+    #[arg(long, requires = "users_file")]
+    leaderboard: bool,
+
+    /// Print only today's contribution count, from one small query (cached for a minute), for shell prompts
+    #[arg(long, conflicts_with_all = ["leaderboard", "org_members", "diff_graph"])]
+    peek: bool,
+
+    /// Rank the members of this organization by total contributions
+    #[arg(long, value_name = "ORG", conflicts_with = "leaderboard")]
+    org_members: Option<String>,
+
+    /// List this organization's recently pushed repositories with everyone's commits, instead of a user's graph
+    #[arg(long, value_name = "ORG", conflicts_with_all = ["username", "leaderboard", "org_members", "peek", "diff_graph", "repo", "top_repos", "output"])]
+    org: Option<String>,
+
+    /// Only cover the last N days (up to a year) instead of GitHub's full calendar
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    since_days: Option<u32>,
+
+    /// First day to cover (YYYY-MM-DD), for a historical window of up to a year
+    #[arg(long, value_name = "DATE", conflicts_with = "since_days")]
+    from: Option<NaiveDate>,
+
+    /// Last day to cover (YYYY-MM-DD) [default: today]
+    #[arg(long, value_name = "DATE", requires = "from")]
+    to: Option<NaiveDate>,
+
+    /// Cover one calendar year, January to December
+    #[arg(long, value_name = "YEAR", conflicts_with_all = ["since_days", "from"])]
+    year: Option<i32>,
+
+    /// Log details about skipped or unusual data to stderr
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Gap between graph cells; by default tight is used only when a spaced year doesn't fit
+    #[arg(long, value_enum)]
+    spacing: Option<Spacing>,
+
+    /// Invert today's cell so it stands out whatever its level
+    #[arg(long)]
+    accent_today: bool,
+
+    /// File with extra GraphQL fields to select on the user, e.g. `followers { totalCount }`
+    #[arg(long, value_name = "FILE")]
+    graphql_extra: Option<std::path::PathBuf>,
+
+    /// Where to write the raw `user` JSON when using --graphql-extra [default: graphql-extra.json]
+    #[arg(long, value_name = "PATH", requires = "graphql_extra")]
+    graphql_extra_out: Option<std::path::PathBuf>,
+
+    /// Under the legend, show how many days and what share of contributions each level holds
+    #[arg(long)]
+    legend_stats: bool,
+
+    /// Print only the grid and the total, without the period summary, legend and repository table
+    #[arg(long)]
+    no_footer: bool,
+
+    /// Shade the days of the current streak in the grid
+    #[arg(long)]
+    highlight_streak: bool,
+
+    /// Print the profile URL and each listed repository's URL (as clickable links where the terminal supports them)
+    #[arg(long)]
+    show_url: bool,
+
+    /// Draw a dim line before each week that starts a new month (only with normal spacing, which has room for it)
+    #[arg(long)]
+    month_separators: bool,
+
+    /// Contributions a day needs to count as active, for the active-day count and streaks
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_ACTIVE_THRESHOLD, value_parser = clap::value_parser!(u32).range(1..))]
+    active_threshold: u32,
+
+    /// Leave Saturdays and Sundays out of the totals, active days, consistency and streaks (the grid still shows them)
+    #[arg(long)]
+    weekdays_only: bool,
+
+    /// First day of the week, for the graph's rows and "this week" [default: Sunday rows like GitHub, Monday for the periods]
+    #[arg(long, value_enum)]
+    week_start: Option<WeekStart>,
+
+    /// Annual contribution target; shows the projected year-end total against it
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    year_goal: Option<u32>,
+
+    /// Also list the repositories pinned on the user's profile
+    #[arg(long)]
+    pinned: bool,
+
+    /// Under each counted repository, list its newest commits from this month
+    #[arg(long)]
+    list_commits: bool,
+
+    /// Don't count a fork's commits that its upstream, also in the list, already has
+    #[arg(long)]
+    dedupe_forks: bool,
+
+    /// Fail instead of falling back to partial or empty data when a request fails or the rate limit runs out
+    #[arg(long)]
+    strict: bool,
+
+    /// Fetch and redraw the graph every SECS seconds (at least 30) until 'q' is pressed
+    #[arg(long, value_name = "SECS", conflicts_with_all = ["output", "benchmark"], value_parser = clap::value_parser!(u64).range(MIN_WATCH_SECS..))]
+    watch: Option<u64>,
+
+    /// Print the number, size and time of API requests and how long each phase took to stderr; skips the cache
+    #[arg(long)]
+    benchmark: bool,
+
+    /// Reuse cached stats that are at most this many minutes old
+    #[arg(long, value_name = "MINUTES", default_value_t = DEFAULT_CACHE_TTL_MINUTES)]
+    cache_ttl: u64,
+
+    /// Always fetch from the API instead of reading the cache (the result is still cached)
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Colors the terminal supports [default: detected from COLORTERM and TERM]
+    #[arg(long, value_enum)]
+    color_depth: Option<ColorDepth>,
+
+    /// Print without ANSI colors, like setting NO_COLOR
+    #[arg(long, conflicts_with = "color_depth")]
+    no_color: bool,
+
+    /// Only list recent repositories whose owner/name matches this regular expression
+    #[arg(long, value_name = "REGEX")]
+    repo_filter: Option<String>,
+
+    /// Only list recent repositories whose primary language is this one (case-insensitive, e.g. rust)
+    #[arg(long, value_name = "LANGUAGE")]
+    lang: Option<String>,
+
+    /// Order of the recent repositories
+    #[arg(long, value_enum, default_value_t = RepoSort::Recent)]
+    sort: RepoSort,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fetch the stats into the on-disk cache without rendering anything, e.g. from cron
+    WarmCache,
 }
 
 #[derive(Debug, Deserialize)]
 struct User {
     login: String,
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    /// "User" or "Organization"
+    #[serde(rename = "type", default)]
+    account_type: String,
 }
 
 
@@ -46,7 +365,8 @@ struct GraphQLResponse {
 
 #[derive(Debug, Deserialize)]
 struct GraphQLData {
-    user: GraphQLUser,
+    user: Option<GraphQLUser>,
+    viewer: Option<User>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,22 +380,52 @@ struct GraphQLUser {
 struct ContributionsCollection {
     #[serde(rename = "contributionCalendar")]
     contribution_calendar: ContributionCalendar,
+    #[serde(flatten)]
+    breakdown: ContributionBreakdown,
 }
 
-#[derive(Debug, Deserialize)]
+/// How the calendar's contributions split by kind
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+struct ContributionBreakdown {
+    #[serde(rename = "totalCommitContributions", default)]
+    commits: u32,
+    #[serde(rename = "totalPullRequestContributions", default)]
+    pull_requests: u32,
+    #[serde(rename = "totalIssueContributions", default)]
+    issues: u32,
+    #[serde(rename = "totalPullRequestReviewContributions", default)]
+    reviews: u32,
+    /// Private contributions the token can't see; only counted when the user shows them on their profile
+    #[serde(rename = "restrictedContributionsCount", default)]
+    restricted: u32,
+}
+
+impl ContributionBreakdown {
+    fn add(self, other: Self) -> Self {
+        Self {
+            commits: self.commits + other.commits,
+            pull_requests: self.pull_requests + other.pull_requests,
+            issues: self.issues + other.issues,
+            reviews: self.reviews + other.reviews,
+            restricted: self.restricted + other.restricted,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 struct ContributionCalendar {
     #[serde(rename = "totalContributions")]
     total_contributions: u32,
     weeks: Vec<GraphQLWeek>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct GraphQLWeek {
     #[serde(rename = "contributionDays")]
     contribution_days: Vec<GraphQLDay>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct GraphQLDay {
     date: String,
     #[serde(rename = "contributionCount")]
@@ -84,11 +434,41 @@ struct GraphQLDay {
     contribution_level: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct Stats {
     username: String,
+    /// True when the token belongs to the queried user, so private contributions are visible
+    #[serde(default)]
+    viewer_is_user: bool,
+    /// True when private contributions were subtracted from a self-query (`--public-only`)
+    #[serde(default)]
+    private_excluded: bool,
+    /// True when the graph was approximated from public events because no token was available
+    #[serde(default)]
+    limited_history: bool,
     contribution_graph: ContributionGraph,
     recent_repos: Vec<RepositoryWithCommits>,
+    /// Repositories pinned on the profile, only fetched with `--pinned`
+    #[serde(default)]
+    pinned_repos: Vec<PinnedRepository>,
+    /// Commits, pull requests, issues and reviews behind the calendar; only the GraphQL calendar has them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    breakdown: Option<ContributionBreakdown>,
+    /// Weekday with the highest average count, derived from the graph after fetching
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    most_active_weekday: Option<WeekdayActivity>,
+    /// Commits by weekday and hour, only collected for `--format punchcard`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    punchcard: Option<Punchcard>,
+}
+
+/// A weekday's contributions across the graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WeekdayActivity {
+    weekday: String,
+    total: u32,
+    /// Contributions per occurrence of the weekday
+    average: f64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -103,7 +483,20 @@ struct Repository {
     pushed_at: String,
     #[serde(rename = "isPrivate")]
     is_private: bool,
+    #[serde(rename = "isFork", default)]
+    is_fork: bool,
+    /// The repository a fork was made from, when it's visible to the token
+    #[serde(default)]
+    parent: Option<RepositoryParent>,
     owner: RepositoryOwner,
+    #[serde(rename = "primaryLanguage", default)]
+    primary_language: Option<Language>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RepositoryParent {
+    #[serde(rename = "nameWithOwner")]
+    name_with_owner: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -111,561 +504,5057 @@ struct RepositoryOwner {
     login: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct RepositoryWithCommits {
     name: String,
     full_name: String,
     pushed_at: String,
     is_private: bool,
+    /// False when the repository was beyond `--scan-repos` and its commits weren't counted
+    #[serde(default = "default_scanned")]
+    scanned: bool,
     today_commits: u32,
     week_commits: u32,
     month_commits: u32,
+    /// Commits whose author date couldn't be parsed, so they're missing from today/week
+    #[serde(default)]
+    unparseable_dates: u32,
+    /// Why counting this repository's commits failed; its counts are zero and not real
+    #[serde(default)]
+    error: Option<String>,
+    /// The newest commits behind the counts, only collected with `--list-commits`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    commits: Vec<CommitSummary>,
 }
 
-#[derive(Debug, Serialize)]
+/// One counted commit, as shown by `--list-commits`
+#[derive(Debug, Serialize, Deserialize)]
+struct CommitSummary {
+    sha: String,
+    date: String,
+    message: String,
+}
+
+/// Per-period commit counts for one repository
+#[derive(Debug, Default)]
+struct CommitCounts {
+    today: u32,
+    week: u32,
+    month: u32,
+    unparseable_dates: u32,
+    commits: Vec<CommitSummary>,
+}
+
+fn default_scanned() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 struct ContributionGraph {
     weeks: Vec<Week>,
     total_contributions: u32,
 }
 
-#[derive(Debug, Serialize)]
+impl ContributionGraph {
+    /// First and last date the data covers, or None for an empty graph
+    fn date_range(&self) -> Option<(&str, &str)> {
+        let mut days = self.weeks.iter().flat_map(|week| &week.days);
+        let first = days.next()?;
+        let last = days.last().unwrap_or(first);
+        Some((&first.date, &last.date))
+    }
+
+    /// Drop the days outside `window`, and weeks left empty by that. GitHub pads the first and
+    /// last week, and the window's UTC bounds can pull in a day on either side, so without this
+    /// every sum over the days would count dates that weren't asked for. Returns whether any were dropped.
+    fn retain_window(&mut self, window: DateWindow) -> bool {
+        let before: usize = self.weeks.iter().map(|week| week.days.len()).sum();
+        for week in &mut self.weeks {
+            week.days.retain(|day| {
+                NaiveDate::parse_from_str(&day.date, "%Y-%m-%d")
+                    .map_or(true, |date| (window.from..=window.to).contains(&date))
+            });
+        }
+        self.weeks.retain(|week| !week.days.is_empty());
+
+        let after: usize = self.weeks.iter().map(|week| week.days.len()).sum();
+        if after == before {
+            return false;
+        }
+        self.total_contributions = self.weeks.iter().flat_map(|week| &week.days).map(|day| day.count).sum();
+        true
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Week {
     days: Vec<Day>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Day {
     date: String,
     count: u32,
     level: u8, // 0-4 for different intensity levels
 }
 
-struct GitHubClient {
-    client: reqwest::Client,
+#[derive(Debug, Deserialize)]
+struct TopReposResponse {
+    data: TopReposData,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopReposData {
+    user: Option<TopReposUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopReposUser {
+    repositories: TopRepositoryConnection,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopRepositoryConnection {
+    nodes: Vec<TopRepository>,
+    #[serde(rename = "pageInfo")]
+    page_info: PageInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageInfo {
+    #[serde(rename = "hasNextPage")]
+    has_next_page: bool,
+    #[serde(rename = "endCursor")]
+    end_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrgReposResponse {
+    data: OrgReposData,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrgReposData {
+    organization: Option<OrgRepositories>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrgRepositories {
+    repositories: RepositoryConnection,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrgMembersResponse {
+    data: OrgMembersData,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrgMembersData {
+    organization: Option<Organization>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Organization {
+    #[serde(rename = "membersWithRole")]
+    members_with_role: OrgMemberConnection,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrgMemberConnection {
+    nodes: Vec<User>,
+    #[serde(rename = "pageInfo")]
+    page_info: PageInfo,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct TopRepository {
+    #[serde(rename = "nameWithOwner")]
+    name_with_owner: String,
+    #[serde(rename = "stargazerCount")]
+    stargazer_count: u32,
+    #[serde(rename = "isPrivate")]
+    is_private: bool,
+    #[serde(rename = "pushedAt")]
+    pushed_at: Option<String>,
+    #[serde(rename = "primaryLanguage")]
+    primary_language: Option<Language>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PinnedResponse {
+    data: PinnedData,
+}
+
+#[derive(Debug, Deserialize)]
+struct PinnedData {
+    user: Option<PinnedUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PinnedUser {
+    #[serde(rename = "pinnedItems")]
+    pinned_items: PinnedItemConnection,
+}
+
+#[derive(Debug, Deserialize)]
+struct PinnedItemConnection {
+    nodes: Vec<PinnedRepository>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct PinnedRepository {
+    #[serde(rename = "nameWithOwner")]
+    name_with_owner: String,
+    #[serde(rename = "stargazerCount")]
+    stargazer_count: u32,
+    description: Option<String>,
+    #[serde(rename = "primaryLanguage")]
+    primary_language: Option<Language>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Language {
+    name: String,
+}
+
+/// GitHub answered with a server error (5xx) on every attempt
+#[derive(Debug)]
+struct ServiceUnavailable {
+    status: u16,
+}
+
+impl std::fmt::Display for ServiceUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GitHub is having issues ({}), try again shortly", self.status)
+    }
+}
+
+impl std::error::Error for ServiceUnavailable {}
+
+/// The account doesn't exist, was deleted or is suspended. REST answers 404 and GraphQL
+/// a null `user` for all of these, so both paths report it the same way.
+#[derive(Debug)]
+struct AccountNotFound {
     username: String,
 }
 
-impl GitHubClient {
-    fn new(username: String, token: Option<String>) -> Result<Self> {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            reqwest::header::USER_AGENT,
-            "GitHub-Stats-CLI-Rust".parse()?,
-        );
-        headers.insert(
-            reqwest::header::ACCEPT,
-            "application/vnd.github.v3+json".parse()?,
-        );
+impl std::fmt::Display for AccountNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Account '{}' not found or suspended", self.username)
+    }
+}
 
-        // Try to get token from gh CLI if not provided
-        let auth_token = if let Some(token) = token {
-            Some(token)
-        } else {
-            Self::get_gh_token().ok()
-        };
+impl std::error::Error for AccountNotFound {}
 
-        if let Some(token) = auth_token {
-            headers.insert(
-                reqwest::header::AUTHORIZATION,
-                format!("Bearer {}", token).parse()?,
-            );
-        }
+/// GitHub couldn't be reached at all (DNS, refused connection), as opposed to an HTTP error
+#[derive(Debug)]
+struct ConnectionFailed {
+    source: reqwest::Error,
+}
 
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .build()?;
+impl std::fmt::Display for ConnectionFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Couldn't reach GitHub, check your network connection ({})", self.source)
+    }
+}
 
-        Ok(Self { client, username })
+impl std::error::Error for ConnectionFailed {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
     }
-    
-    fn get_gh_token() -> Result<String> {
-        let output = std::process::Command::new("gh")
-            .args(&["auth", "token"])
-            .output()
-            .context("Failed to run 'gh auth token' command")?;
-        
-        if !output.status.success() {
-            anyhow::bail!("gh CLI not authenticated. Run 'gh auth login' first.");
-        }
-        
-        let token = String::from_utf8(output.stdout)?
-            .trim()
-            .to_string();
-            
-        if token.is_empty() {
-            anyhow::bail!("No token returned from gh CLI");
-        }
-        
-        Ok(token)
+}
+
+/// A request got no (complete) response within `--timeout`
+#[derive(Debug)]
+struct RequestTimedOut {
+    after: Duration,
+}
+
+impl std::fmt::Display for RequestTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Request to GitHub timed out after {}s (raise it with --timeout)", self.after.as_secs())
     }
+}
 
-    async fn get_user(&self) -> Result<User> {
-        let url = format!("https://api.github.com/users/{}", self.username);
-        let response = self.client.get(&url).send().await?;
-        
-        if response.status() == 404 {
-            anyhow::bail!("User '{}' not found", self.username);
-        }
-        
-        let user: User = response.json().await?;
-        Ok(user)
+impl std::error::Error for RequestTimedOut {}
+
+/// The run spent its `--max-total-retry-time` on backoff and rate-limit waits, so nothing retries anymore
+#[derive(Debug)]
+struct RetryBudgetExhausted {
+    budget: Duration,
+    succeeded: u32,
+    made: u32,
+}
+
+impl std::fmt::Display for RetryBudgetExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Gave up after waiting {}s on retries (--max-total-retry-time); {} of {} requests had succeeded",
+            self.budget.as_secs(),
+            self.succeeded,
+            self.made
+        )
     }
+}
 
-    
-    async fn get_data_from_graphql(&self) -> Result<(ContributionGraph, Vec<RepositoryWithCommits>)> {
-        let query = r#" 
-        query($username: String!) {
-            user(login: $username) {
-                contributionsCollection {
-                    contributionCalendar {
-                        totalContributions
-                        weeks {
-                            contributionDays {
-                                date
-                                contributionCount
-                                contributionLevel
-                            }
-                        }
-                    }
-                }
-                repositories(
-                    first: 5
-                    orderBy: {field: PUSHED_AT, direction: DESC}
-                    affiliations: [OWNER, COLLABORATOR, ORGANIZATION_MEMBER]
-                    ownerAffiliations: [OWNER, COLLABORATOR, ORGANIZATION_MEMBER]
-                ) {
-                    nodes {
-                        name
-                        pushedAt
-                        isPrivate
-                        owner {
-                            login
-                        }
+impl std::error::Error for RetryBudgetExhausted {}
+
+/// GitHub refused a request for the rate limit and it doesn't reset soon enough to wait for
+#[derive(Debug)]
+struct RateLimited {
+    resets_at: DateTime<Utc>,
+    /// Zone the reset time is shown in
+    zone: Tz,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let minutes = (self.resets_at - Utc::now()).num_minutes().max(0) + 1;
+        write!(
+            f,
+            "GitHub's API rate limit is exhausted, try again after {} (in about {} minutes)",
+            self.resets_at.with_timezone(&self.zone).format("%H:%M:%S"),
+            minutes
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// The first entry of a GraphQL response's `errors` array. GitHub answers these with HTTP 200,
+/// so without looking at the body a failed query reads like one that found nothing.
+#[derive(Debug)]
+struct GraphQLFailure {
+    message: String,
+}
+
+impl std::fmt::Display for GraphQLFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GitHub's GraphQL API returned an error: {}", self.message)
+    }
+}
+
+impl std::error::Error for GraphQLFailure {}
+
+/// One entry of a GraphQL response's top-level `errors` array
+#[derive(Debug, Deserialize)]
+struct GraphQLError {
+    message: String,
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+}
+
+/// Fail on the first error of a GraphQL response. A NOT_FOUND only nulls the field it's about,
+/// which callers already report (as `AccountNotFound`, for instance), so it doesn't fail here.
+fn check_graphql_errors(body: &serde_json::Value, headers: &reqwest::header::HeaderMap, zone: Tz) -> Result<()> {
+    let Some(errors) = body.get("errors") else {
+        return Ok(());
+    };
+    let errors: Vec<GraphQLError> = serde_json::from_value(errors.clone())
+        .context("GitHub's GraphQL response has a malformed errors array")?;
+    let Some(error) = errors.into_iter().find(|error| error.kind.as_deref() != Some("NOT_FOUND")) else {
+        return Ok(());
+    };
+    if error.kind.as_deref() == Some("RATE_LIMITED") {
+        return Err(RateLimited { resets_at: Utc::now() + rate_limit_wait(headers), zone }.into());
+    }
+    Err(GraphQLFailure { message: error.message }.into())
+}
+
+/// `--max-total-retry-time` value: seconds, optionally suffixed with s, m or h
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, unit) = match value.trim().strip_suffix(['s', 'm', 'h']) {
+        Some(number) => (number, value.trim().chars().last().unwrap_or('s')),
+        None => (value.trim(), 's'),
+    };
+    let amount: u64 = number.trim().parse().map_err(|_| format!("'{}' is not a duration like 60s or 2m", value))?;
+    let seconds = match unit {
+        'm' => amount * 60,
+        'h' => amount * 60 * 60,
+        _ => amount,
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Map the status of a REST user lookup; suspended and deleted accounts both answer 404 (or 410)
+fn check_user_response(username: &str, status: reqwest::StatusCode) -> Result<()> {
+    match status.as_u16() {
+        404 | 410 => Err(AccountNotFound { username: username.to_string() }.into()),
+        _ if !status.is_success() => anyhow::bail!("Looking up '{}' failed: HTTP {}", username, status),
+        _ => Ok(()),
+    }
+}
+
+/// GraphQL's counterpart of `check_user_response`: a missing account is a null `user`
+fn require_user<T>(username: &str, user: Option<T>) -> Result<T> {
+    user.ok_or_else(|| AccountNotFound { username: username.to_string() }.into())
+}
+
+/// One `*ContributionsByRepository` list of `contributionsCollection`
+#[derive(Clone, Copy)]
+struct ContributionKind {
+    field: &'static str,
+    /// Fields of each contribution node
+    nodes: &'static str,
+    /// What a node is, for messages
+    label: &'static str,
+}
+
+const PRIVATE_CONTRIBUTION_KINDS: [ContributionKind; 4] = [
+    ContributionKind { field: "commitContributionsByRepository", nodes: "occurredAt commitCount", label: "days of commits" },
+    ContributionKind { field: "issueContributionsByRepository", nodes: "occurredAt", label: "issues" },
+    ContributionKind { field: "pullRequestContributionsByRepository", nodes: "occurredAt", label: "pull requests" },
+    ContributionKind { field: "pullRequestReviewContributionsByRepository", nodes: "occurredAt", label: "reviews" },
+];
+
+/// GitHub's limit on `maxRepositories`; a list this long may have been cut short
+const MAX_CONTRIBUTION_REPOSITORIES: usize = 100;
+
+/// The query behind `--public-only`: each kind's contributions per repository, from `$after` when `paged`
+fn private_contributions_query(kinds: &[ContributionKind], paged: bool) -> String {
+    let selections: String = kinds.iter()
+        .map(|kind| format!(
+            "{}(maxRepositories: {}) {{ repository {{ nameWithOwner isPrivate }} contributions(first: 100{}) {{ totalCount pageInfo {{ hasNextPage endCursor }} nodes {{ {} }} }} }}\n",
+            kind.field,
+            MAX_CONTRIBUTION_REPOSITORIES,
+            if paged { ", after: $after" } else { "" },
+            kind.nodes
+        ))
+        .collect();
+    format!(
+        "query($username: String!, $from: DateTime, $to: DateTime{}) {{ user(login: $username) {{ contributionsCollection(from: $from, to: $to) {{\n{}}} }} }}",
+        if paged { ", $after: String" } else { "" },
+        selections
+    )
+}
+
+/// A 403/429 that GitHub sent because the rate limit ran out, not for lack of access
+fn is_rate_limited(response: &reqwest::Response) -> bool {
+    match response.status().as_u16() {
+        429 => true,
+        403 => response.headers()
+            .get("x-ratelimit-remaining")
+            .is_some_and(|remaining| remaining.as_bytes() == b"0")
+            || response.headers().contains_key(reqwest::header::RETRY_AFTER),
+        _ => false,
+    }
+}
+
+/// How long a rate-limited response asks to wait: `Retry-After` (secondary limits) if sent,
+/// otherwise until `X-RateLimit-Reset`, plus a second so the retry doesn't race the reset
+fn rate_limit_wait(headers: &reqwest::header::HeaderMap) -> Duration {
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<i64>().ok();
+    if let Some(seconds) = header("retry-after") {
+        return Duration::from_secs(seconds.max(0) as u64);
+    }
+    match header("x-ratelimit-reset") {
+        Some(reset) => Duration::from_secs((reset - Utc::now().timestamp()).max(0) as u64 + 1),
+        None => DEFAULT_RATE_LIMIT_WAIT,
+    }
+}
+
+/// Whether a paginated REST response's `Link` header points at a next page; None without the header
+fn link_has_next(headers: &reqwest::header::HeaderMap) -> Option<bool> {
+    let link = headers.get(reqwest::header::LINK)?.to_str().ok()?;
+    Some(link.split(',').any(|part| part.split(';').skip(1).any(|param| param.trim() == "rel=\"next\"")))
+}
+
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+}
+
+/// Wait before retry number `attempt` (from 1): the base delay doubled per attempt, plus up to
+/// half of that again by `jitter` (0 to 1), so clients that failed together don't retry together
+fn backoff_delay(attempt: u32, jitter: f64) -> Duration {
+    let delay = TRANSIENT_RETRY_BASE_DELAY * 2u32.pow(attempt.saturating_sub(1));
+    delay + delay.mul_f64(jitter.clamp(0.0, 1.0) / 2.0)
+}
+
+/// A number from 0 to 1 that differs between calls, which is all backoff jitter needs
+fn random_fraction() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
+    hasher.finish() as f64 / u64::MAX as f64
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rgb(u8, u8, u8);
+
+impl Rgb {
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Named color scheme: a background and one color per contribution level
+#[derive(Debug)]
+struct Theme {
+    name: &'static str,
+    background: Rgb,
+    levels: [Rgb; 5],
+    /// Words on either side of the text legend
+    legend: [&'static str; 2],
+    /// Level cells for the text graph instead of the default squares
+    symbols: Option<[&'static str; 5]>,
+}
+
+const THEMES: &[Theme] = &[
+    Theme {
+        name: "dark",
+        background: Rgb(0x0d, 0x11, 0x17),
+        levels: [
+            Rgb(0x16, 0x1b, 0x22),
+            Rgb(0x0e, 0x44, 0x29),
+            Rgb(0x00, 0x6d, 0x32),
+            Rgb(0x26, 0xa6, 0x41),
+            Rgb(0x39, 0xd3, 0x53),
+        ],
+        legend: DEFAULT_LEGEND,
+        symbols: None,
+    },
+    Theme {
+        name: "light",
+        background: Rgb(0xff, 0xff, 0xff),
+        levels: [
+            Rgb(0xeb, 0xed, 0xf0),
+            Rgb(0x9b, 0xe9, 0xa8),
+            Rgb(0x40, 0xc4, 0x63),
+            Rgb(0x30, 0xa1, 0x4e),
+            Rgb(0x21, 0x6e, 0x39),
+        ],
+        legend: DEFAULT_LEGEND,
+        symbols: None,
+    },
+];
+
+const DEFAULT_LEGEND: [&str; 2] = ["Less", "More"];
+
+fn find_theme(name: &str) -> Result<&'static Theme> {
+    THEMES.iter()
+        .find(|theme| theme.name.eq_ignore_ascii_case(name))
+        .with_context(|| {
+            let names: Vec<&str> = THEMES.iter().map(|theme| theme.name).collect();
+            format!("Unknown theme '{}' (available: {})", name, names.join(", "))
+        })
+}
+
+/// A `--theme-file`: like the built-in themes, but colors are hex strings
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    /// Defaults to the file name
+    name: Option<String>,
+    /// SVG and HTML background; defaults to the dark theme's
+    background: Option<String>,
+    levels: [String; 5],
+    legend: [String; 2],
+    symbols: Option<[String; 5]>,
+}
+
+/// Parse `#rrggbb` (the `#` is optional) or the short `#rgb`
+fn parse_hex_color(text: &str) -> Result<Rgb> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    let digits: Vec<u8> = hex.chars()
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<_>>()
+        .with_context(|| format!("'{}' is not a hex color like #39d353", text))?;
+    match digits[..] {
+        [r, g, b] => Ok(Rgb(r * 17, g * 17, b * 17)),
+        [r1, r2, g1, g2, b1, b2] => Ok(Rgb(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+        _ => anyhow::bail!("'{}' is not a hex color like #39d353", text),
+    }
+}
+
+/// Read and validate a `--theme-file`. It lives for the whole run like the built-in themes,
+/// so it's leaked to get the same `'static` lifetime.
+fn load_theme_file(path: &std::path::Path) -> Result<&'static Theme> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: ThemeFile = serde_json::from_str(&text)
+        .with_context(|| format!("Invalid theme file {}", path.display()))?;
+
+    let mut levels = [Rgb(0, 0, 0); 5];
+    for (level, (color, text)) in levels.iter_mut().zip(&file.levels).enumerate() {
+        *color = parse_hex_color(text).with_context(|| format!("Bad color for level {} in {}", level, path.display()))?;
+    }
+    let background = match &file.background {
+        Some(text) => parse_hex_color(text).with_context(|| format!("Bad background color in {}", path.display()))?,
+        None => THEMES[0].background,
+    };
+    if let Some(symbols) = &file.symbols {
+        if symbols.iter().any(|symbol| symbol.is_empty()) {
+            anyhow::bail!("Empty symbol in {}", path.display());
+        }
+    }
+
+    let leak = |text: String| -> &'static str { Box::leak(text.into_boxed_str()) };
+    let name = file.name.unwrap_or_else(|| {
+        path.file_stem().map_or_else(|| "custom".to_string(), |stem| stem.to_string_lossy().into_owned())
+    });
+    let [less, more] = file.legend;
+    Ok(Box::leak(Box::new(Theme {
+        name: leak(name),
+        background,
+        levels,
+        legend: [leak(less), leak(more)],
+        symbols: file.symbols.map(|symbols| symbols.map(leak)),
+    })))
+}
+
+/// Print every theme name, followed by its five level colors when colors are enabled
+fn list_themes() {
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+    for theme in THEMES {
+        if !colorize {
+            println!("{}", theme.name);
+            continue;
+        }
+        let preview: Vec<String> = theme.levels.iter()
+            .map(|color| "■".truecolor(color.0, color.1, color.2).to_string())
+            .collect();
+        println!("{:<12} {}", theme.name, preview.join(" "));
+    }
+}
+
+/// Budget of one GitHub rate-limit bucket, as last reported by the response headers
+#[derive(Debug, Clone, Copy)]
+struct RateBudget {
+    remaining: u32,
+    /// Unix timestamp at which the bucket refills
+    reset: i64,
+}
+
+impl RateBudget {
+    fn exhausted(&self) -> bool {
+        self.remaining == 0 && self.reset > Utc::now().timestamp()
+    }
+}
+
+/// Tracks rate-limit headers across requests and decides how long to hold off before the next one.
+/// Every token has its own budgets, keyed by its index in `GitHubClient::tokens`.
+#[derive(Debug, Default)]
+struct RateLimiter {
+    budgets: HashMap<(usize, &'static str), RateBudget>,
+    requests_made: u32,
+    /// Token the next request goes out with
+    active_token: usize,
+    /// Time spent so far waiting before retries, counted against `--max-total-retry-time`
+    retry_waited: Duration,
+    /// Set once the retry budget ran out; every later request fails right away
+    retry_budget_exhausted: bool,
+}
+
+impl RateLimiter {
+    /// Delay before the next request on `bucket` with the active token, and whether its bucket is exhausted
+    fn delay_for(&self, bucket: &'static str) -> Option<(Duration, bool)> {
+        let budget = self.budgets.get(&(self.active_token, bucket))?;
+        let until_reset = (budget.reset - Utc::now().timestamp()).max(0) as u64;
+
+        if budget.remaining == 0 {
+            // Wait one extra second so we don't race the reset
+            Some((Duration::from_secs(until_reset + 1), true))
+        } else if budget.remaining < RATE_LIMIT_PACING_THRESHOLD {
+            // Spread what's left evenly over the time until reset
+            Some((Duration::from_secs(until_reset) / budget.remaining, false))
+        } else {
+            None
+        }
+    }
+
+    fn record(&mut self, token: usize, bucket: &'static str, headers: &reqwest::header::HeaderMap) {
+        let header = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+        if let (Some(remaining), Some(reset)) = (header("x-ratelimit-remaining"), header("x-ratelimit-reset")) {
+            self.budgets.insert((token, bucket), RateBudget {
+                remaining: remaining.max(0) as u32,
+                reset,
+            });
+        }
+    }
+
+    /// Switch to the next of `token_count` tokens whose `bucket` isn't known to be exhausted.
+    /// Returns the new token's index, or None when every other token is out of budget too.
+    fn rotate(&mut self, bucket: &'static str, token_count: usize) -> Option<usize> {
+        let next = (1..token_count)
+            .map(|offset| (self.active_token + offset) % token_count)
+            .find(|token| !self.budgets.get(&(*token, bucket)).is_some_and(RateBudget::exhausted))?;
+        self.active_token = next;
+        Some(next)
+    }
+}
+
+/// What the requests of a run cost, for `--benchmark`
+#[derive(Debug, Default, Clone, Copy)]
+struct RequestMetrics {
+    graphql_requests: u32,
+    rest_requests: u32,
+    /// Requests answered with a 2xx status
+    succeeded: u32,
+    /// Body bytes as announced by Content-Length; responses without one aren't counted
+    bytes: u64,
+    /// Time spent waiting for responses, summed over requests (concurrent ones overlap)
+    request_time: Duration,
+    /// Time spent holding off for the rate limit
+    rate_limit_wait: Duration,
+}
+
+async fn wait_with_countdown(wait: Duration) {
+    let mut seconds = wait.as_secs();
+    while seconds > 0 {
+        eprint!("\r{} resuming in {}s ", "Rate limit reached,".bright_yellow(), seconds);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        seconds -= 1;
+    }
+    eprint!("\r\x1b[2K");
+}
+
+/// How much a client is allowed to fetch
+#[derive(Debug, Clone)]
+struct ClientOptions {
+    /// Abort after this many requests
+    max_requests: Option<u32>,
+    /// Total time the run may spend waiting before retries
+    retry_budget: Option<Duration>,
+    /// Per-request limit, connecting and reading the body included
+    timeout: Duration,
+    /// Where requests go
+    endpoints: ApiEndpoints,
+    /// Ask the gh CLI for a token when none was given
+    gh_fallback: bool,
+    /// Pages of 100 commits fetched per repository before giving up
+    max_pages: u32,
+    /// Recently pushed repositories to list
+    repo_count: usize,
+    /// Listed repositories that get the (expensive) commit-count round-trip
+    scan_repos: usize,
+    /// Strip private contributions from a self-query's calendar
+    public_only: bool,
+    /// Restrict the calendar to these dates instead of the trailing year
+    window: Option<DateWindow>,
+    /// Log skipped data to stderr
+    verbose: bool,
+    /// User-supplied fields to add to the calendar query
+    graphql_extra: Option<GraphqlExtra>,
+    /// Fetch the profile's pinned repositories
+    pinned: bool,
+    /// Keep the newest commits of each counted repository
+    list_commits: bool,
+    /// Bucket the counted commits by weekday and hour
+    punchcard: bool,
+    /// Count every author's commits instead of the user's, for an organization's repositories
+    all_authors: bool,
+    /// Leave out fork commits that were counted in the listed upstream
+    dedupe_forks: bool,
+    /// Where a repository's "this week" commits start
+    week_start: WeekStart,
+    /// Zone that days and commit windows follow
+    timezone: Tz,
+    /// Turn every swallowed failure into an error
+    strict: bool,
+    /// Keep only recent repositories whose owner/name matches
+    repo_filter: Option<regex::Regex>,
+    /// Keep only recent repositories with this primary language
+    language: Option<String>,
+    /// Where the graph's counts come from
+    source: GraphSource,
+}
+
+/// REST base and GraphQL endpoint of the GitHub instance to talk to
+#[derive(Debug, Clone)]
+struct ApiEndpoints {
+    rest: String,
+    graphql: String,
+}
+
+impl ApiEndpoints {
+    /// Without an explicit GraphQL URL, derive it like GitHub lays them out: api.github.com/graphql
+    /// next to api.github.com, and HOST/api/graphql next to HOST/api/v3 on Enterprise Server
+    fn new(rest: &str, graphql: Option<&str>) -> Self {
+        let rest = rest.trim_end_matches('/').to_string();
+        let graphql = match graphql {
+            Some(graphql) => graphql.trim_end_matches('/').to_string(),
+            None => match rest.strip_suffix("/v3") {
+                Some(api) => format!("{}/graphql", api),
+                None => format!("{}/graphql", rest),
+            },
+        };
+        Self { rest, graphql }
+    }
+
+    /// Endpoints of the instance at `host`, with or without a scheme: HOST/api/v3 and
+    /// HOST/api/graphql, or the public API for github.com
+    fn for_host(host: &str, graphql: Option<&str>) -> Self {
+        let host = host.trim_end_matches('/');
+        let base = if host.contains("://") { host.to_string() } else { format!("https://{}", host) };
+        if base == "https://github.com" {
+            return Self::new(DEFAULT_API_URL, graphql);
+        }
+        Self::new(&format!("{}/api/v3", base), graphql)
+    }
+
+    /// REST URL for `path`, which starts with a slash
+    fn rest(&self, path: &str) -> String {
+        format!("{}{}", self.rest, path)
+    }
+
+    /// Host to ask `gh` for a token of, or None for github.com, which is what `gh` defaults to
+    fn gh_hostname(&self) -> Option<String> {
+        let url = reqwest::Url::parse(&self.rest).ok()?;
+        let host = url.host_str()?;
+        (host != "api.github.com").then(|| host.to_string())
+    }
+}
+
+/// Fields from `--graphql-extra` and where their raw result goes
+#[derive(Debug, Clone)]
+struct GraphqlExtra {
+    fields: String,
+    output: std::path::PathBuf,
+}
+
+/// Inclusive range of local dates a graph covers
+#[derive(Debug, Clone, Copy)]
+struct DateWindow {
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+impl DateWindow {
+    /// The last `days` days up to and including today, clamped to what one query can cover
+    fn since_days(days: u32, zone: Tz) -> Self {
+        let days = if days > MAX_WINDOW_DAYS {
+            eprintln!("{}", format!("--since-days is limited to {} days by GitHub, clamping", MAX_WINDOW_DAYS).bright_yellow());
+            MAX_WINDOW_DAYS
+        } else {
+            days
+        };
+        let to = today_in(zone);
+        Self {
+            from: to - chrono::Duration::days(days as i64 - 1),
+            to,
+        }
+    }
+
+    /// `--from`/`--to`, ending today when there's no `to`. GitHub answers at most a year per
+    /// query, and unlike `--since-days` an explicit range is rejected rather than clamped.
+    fn between(from: NaiveDate, to: Option<NaiveDate>, zone: Tz) -> Result<Self> {
+        let to = to.unwrap_or_else(|| today_in(zone));
+        if to < from {
+            anyhow::bail!("--to {} is before --from {}", to, from);
+        }
+        let year_later = from.checked_add_months(chrono::Months::new(12)).unwrap_or(NaiveDate::MAX);
+        if to >= year_later {
+            anyhow::bail!("--from {} to {} spans more than a year, which GitHub doesn't answer in one query; the latest --to is {}",
+                from, to, year_later.pred_opt().unwrap_or(year_later));
+        }
+        Ok(Self { from, to })
+    }
+
+    /// January 1st to December 31st of `year`; the current year ends today in effect, a later one is rejected
+    fn year(year: i32, zone: Tz) -> Result<Self> {
+        let today = today_in(zone);
+        if year > today.year() {
+            anyhow::bail!("--year {} hasn't started yet", year);
+        }
+        match (NaiveDate::from_ymd_opt(year, 1, 1), NaiveDate::from_ymd_opt(year, 12, 31)) {
+            (Some(from), Some(to)) => Ok(Self { from, to }),
+            _ => anyhow::bail!("--year {} is out of range", year),
+        }
+    }
+
+    /// Fewest calendar weeks a complete answer for this window can have
+    fn expected_weeks(&self) -> usize {
+        ((self.to - self.from).num_days() as usize + 1) / 7
+    }
+
+    /// GitHub's default: 52 full weeks plus the current one, starting on a Sunday
+    fn trailing_year(zone: Tz) -> Self {
+        let to = today_in(zone);
+        Self {
+            from: to
+                - chrono::Duration::weeks(WEEKS_IN_YEAR as i64)
+                - chrono::Duration::days(to.weekday().num_days_from_sunday() as i64),
+            to,
+        }
+    }
+
+    /// Midnight in `zone` at the start of `from`, as the UTC timestamp the APIs expect
+    fn start_timestamp(&self, zone: Tz) -> String {
+        local_day_start(&zone, self.from)
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string()
+    }
+
+    /// The end of the window; today's window ends now so commits made later aren't asked for
+    fn end_timestamp(&self, zone: Tz) -> String {
+        let end = local_day_end(&zone, self.to);
+        end.min(Utc::now()).format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+
+    /// The end of the window for the commits API: the last second of `to` even when that's still
+    /// ahead, so the URL, and with it the cached ETag, stays the same all day
+    fn commits_until(&self, zone: Tz) -> String {
+        local_day_end(&zone, self.to).format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+}
+
+const END_OF_DAY: NaiveTime = match NaiveTime::from_hms_opt(23, 59, 59) {
+    Some(time) => time,
+    None => NaiveTime::MIN,
+};
+const DST_GAP_STEP_MINUTES: i64 = 15; // every zone's offsets are whole quarter hours
+const DST_GAP_MAX_STEPS: usize = 4 * 24;
+
+/// UTC instant of a wall-clock time in `zone`. A time that falls into a DST gap (spring forward)
+/// doesn't exist, so it is moved forward to the first one that does, or backward with `latest`.
+/// A time that occurs twice (fall back) resolves to its earlier occurrence, or the later with `latest`.
+fn resolve_local_time<Tz: TimeZone>(zone: &Tz, local: NaiveDateTime, latest: bool) -> DateTime<Utc> {
+    let step = chrono::Duration::minutes(if latest { -DST_GAP_STEP_MINUTES } else { DST_GAP_STEP_MINUTES });
+    let mut candidate = local;
+    for _ in 0..DST_GAP_MAX_STEPS {
+        match zone.from_local_datetime(&candidate) {
+            chrono::LocalResult::Single(time) => return time.with_timezone(&Utc),
+            // chrono doesn't order the two occurrences consistently across platforms
+            chrono::LocalResult::Ambiguous(first, second) => {
+                let (first, second) = (first.with_timezone(&Utc), second.with_timezone(&Utc));
+                return if latest { first.max(second) } else { first.min(second) };
+            }
+            chrono::LocalResult::None => candidate += step,
+        }
+    }
+    // No zone skips a whole day's worth of quarter hours, but don't loop on a broken one
+    local.and_utc()
+}
+
+/// First instant of `date` in `zone`
+fn local_day_start<Tz: TimeZone>(zone: &Tz, date: NaiveDate) -> DateTime<Utc> {
+    resolve_local_time(zone, date.and_time(NaiveTime::MIN), false)
+}
+
+/// Last second of `date` in `zone`
+fn local_day_end<Tz: TimeZone>(zone: &Tz, date: NaiveDate) -> DateTime<Utc> {
+    resolve_local_time(zone, date.and_time(END_OF_DAY), true)
+}
+
+/// Today's date in `zone`
+fn today_in(zone: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&zone).date_naive()
+}
+
+/// The first of the month `date` falls in
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date - chrono::Duration::days(date.day0() as i64)
+}
+
+/// January 1st of the year `date` falls in
+fn first_of_year(date: NaiveDate) -> NaiveDate {
+    date - chrono::Duration::days(date.ordinal0() as i64)
+}
+
+struct GitHubClient {
+    client: reqwest::Client,
+    username: String,
+    /// Authorization header values, rotated through when one token's rate limit runs out
+    tokens: Arc<Vec<reqwest::header::HeaderValue>>,
+    /// Shared between clients created with `for_user` so batch runs draw from one budget
+    rate_limiter: Arc<Mutex<RateLimiter>>,
+    metrics: Arc<Mutex<RequestMetrics>>,
+    options: ClientOptions,
+    authenticated: bool,
+}
+
+impl GitHubClient {
+    fn new(username: String, tokens: Vec<String>, options: ClientOptions) -> Result<Self> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            "GitHub-Stats-CLI-Rust".parse()?,
+        );
+        headers.insert(
+            reqwest::header::ACCEPT,
+            "application/vnd.github.v3+json".parse()?,
+        );
+
+        // Without an explicit token, try the one Actions and most CI set, then the gh CLI's
+        let env_token = std::env::var("GITHUB_TOKEN").ok().filter(|token| !token.is_empty());
+        let tokens = if !tokens.is_empty() {
+            tokens
+        } else if let Some(token) = env_token {
+            vec![token]
+        } else if options.gh_fallback {
+            Self::get_gh_token(options.endpoints.gh_hostname().as_deref()).into_iter().collect()
+        } else {
+            Vec::new()
+        };
+        let tokens = tokens.iter()
+            .map(|token| {
+                let mut value: reqwest::header::HeaderValue = format!("Bearer {}", token).parse()
+                    .context("Token contains characters that can't be sent in a header")?;
+                value.set_sensitive(true);
+                Ok(value)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let authenticated = !tokens.is_empty();
+
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(options.timeout)
+            .connect_timeout(options.timeout)
+            .build()?;
+
+        Ok(Self {
+            client,
+            username,
+            tokens: Arc::new(tokens),
+            rate_limiter: Arc::new(Mutex::new(RateLimiter::default())),
+            metrics: Arc::new(Mutex::new(RequestMetrics::default())),
+            options,
+            authenticated,
+        })
+    }
+
+    /// Client for another user that shares the connection pool, tokens and rate-limit budget
+    fn for_user(&self, username: String) -> Self {
+        Self {
+            client: self.client.clone(),
+            username,
+            tokens: Arc::clone(&self.tokens),
+            rate_limiter: Arc::clone(&self.rate_limiter),
+            metrics: Arc::clone(&self.metrics),
+            options: self.options.clone(),
+            authenticated: self.authenticated,
+        }
+    }
+
+    /// Send a request, pacing it against the rate-limit budget and the `--max-requests` cap.
+    /// With several tokens, a token that runs out of budget is swapped for the next one instead of waiting.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let request = request.build()?;
+        let bucket = if request.url().path().ends_with("/graphql") { "graphql" } else { "core" };
+
+        let mut rotations = 0;
+        let mut rate_limit_retries = 0;
+        loop {
+            let mut attempt = request.try_clone().context("Request body can't be resent")?;
+            let (token, delay) = {
+                let mut limiter = self.rate_limiter.lock().unwrap();
+                if limiter.retry_budget_exhausted {
+                    drop(limiter);
+                    return Err(self.retry_budget_error());
+                }
+                if let Some(max_requests) = self.options.max_requests {
+                    if limiter.requests_made >= max_requests {
+                        anyhow::bail!("Reached the limit of {} API requests (--max-requests)", max_requests);
+                    }
+                }
+                limiter.requests_made += 1;
+                if limiter.delay_for(bucket).is_some_and(|(_, exhausted)| exhausted) {
+                    limiter.rotate(bucket, self.tokens.len());
+                }
+                (limiter.active_token, limiter.delay_for(bucket))
+            };
+
+            match delay {
+                Some((wait, true)) if self.options.strict => {
+                    anyhow::bail!("Rate limit exhausted, it resets in {}s (--strict doesn't wait)", wait.as_secs());
+                }
+                Some((wait, true)) => self.pause_for_retry(wait, true).await?,
+                Some((wait, false)) => tokio::time::sleep(wait).await,
+                None => {}
+            }
+            if let Some((wait, _)) = delay {
+                self.metrics.lock().unwrap().rate_limit_wait += wait;
+            }
+
+            if let Some(authorization) = self.tokens.get(token) {
+                attempt.headers_mut().insert(reqwest::header::AUTHORIZATION, authorization.clone());
+            }
+            let started = std::time::Instant::now();
+            let response = self.client.execute(attempt).await;
+            {
+                let mut metrics = self.metrics.lock().unwrap();
+                match bucket {
+                    "graphql" => metrics.graphql_requests += 1,
+                    _ => metrics.rest_requests += 1,
+                }
+                metrics.request_time += started.elapsed();
+                if let Ok(response) = &response {
+                    metrics.bytes += response.content_length().unwrap_or(0);
+                    if response.status().is_success() {
+                        metrics.succeeded += 1;
                     }
                 }
             }
+            let response = response.map_err(|e| self.request_error(e))?;
+
+            let rotated = {
+                let mut limiter = self.rate_limiter.lock().unwrap();
+                limiter.record(token, bucket, response.headers());
+                if !is_rate_limited(&response) || rotations + 1 >= self.tokens.len() {
+                    None
+                } else if limiter.active_token == token {
+                    limiter.rotate(bucket, self.tokens.len())
+                } else {
+                    // Another request already moved on from this token
+                    Some(limiter.active_token)
+                }
+            };
+            if let Some(next) = rotated {
+                rotations += 1;
+                if self.options.verbose {
+                    eprintln!("{}", format!("Token {} hit the rate limit, retrying with token {}", token + 1, next + 1).bright_black());
+                }
+                continue;
+            }
+
+            // Every token is out, so the request is only worth repeating if the limit lifts soon
+            if is_rate_limited(&response) {
+                let wait = rate_limit_wait(response.headers());
+                if self.options.strict || wait > MAX_RATE_LIMIT_SLEEP || rate_limit_retries >= MAX_RATE_LIMIT_RETRIES {
+                    return Err(RateLimited { resets_at: Utc::now() + wait, zone: self.options.timezone }.into());
+                }
+                rate_limit_retries += 1;
+                self.pause_for_retry(wait, true).await?;
+                self.metrics.lock().unwrap().rate_limit_wait += wait;
+                continue;
+            }
+            return Ok(response);
         }
-        "#;
-        
-        let variables = serde_json::json!({
-            "username": self.username
-        });
-        
-        let request_body = serde_json::json!({
-            "query": query,
-            "variables": variables
+    }
+    
+    /// `send`, but timeouts and server errors are retried with jittered exponential backoff.
+    /// Every other status, 401, 404 and 422 included, is returned at once for the caller to handle.
+    async fn send_with_retries(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let retry = request.try_clone().context("Request body can't be resent")?;
+            let result = self.send(retry).await;
+            let failure = match &result {
+                Ok(response) if is_transient_status(response.status()) => format!("got {}", response.status()),
+                Err(e) if e.is::<RequestTimedOut>() => "timed out".to_string(),
+                _ => return result,
+            };
+
+            attempt += 1;
+            if attempt >= TRANSIENT_RETRY_ATTEMPTS {
+                return match result {
+                    Ok(response) => Err(ServiceUnavailable { status: response.status().as_u16() }.into()),
+                    Err(e) => Err(e),
+                };
+            }
+            let wait = backoff_delay(attempt, random_fraction());
+            if self.options.verbose {
+                eprintln!("{}", format!("Attempt {} {}, retrying in {}ms", attempt, failure, wait.as_millis()).bright_black());
+            }
+            self.pause_for_retry(wait, false).await?;
+        }
+    }
+
+    /// Wait `wait` before retrying, charged against `--max-total-retry-time`. Once the budget
+    /// can't cover it, this and every later request of the run fails instead.
+    async fn pause_for_retry(&self, wait: Duration, rate_limited: bool) -> Result<()> {
+        if let Some(budget) = self.options.retry_budget {
+            let exhausted = {
+                let mut limiter = self.rate_limiter.lock().unwrap();
+                if limiter.retry_waited + wait > budget {
+                    limiter.retry_budget_exhausted = true;
+                    true
+                } else {
+                    limiter.retry_waited += wait;
+                    false
+                }
+            };
+            if exhausted {
+                return Err(self.retry_budget_error());
+            }
+        }
+
+        // Rate-limit resets are long enough to be worth a visible countdown
+        if rate_limited {
+            wait_with_countdown(wait).await;
+        } else {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    fn retry_budget_error(&self) -> anyhow::Error {
+        let metrics = self.metrics();
+        RetryBudgetExhausted {
+            budget: self.options.retry_budget.unwrap_or_default(),
+            succeeded: metrics.succeeded,
+            made: metrics.graphql_requests + metrics.rest_requests,
+        }
+        .into()
+    }
+
+    /// Typed error for a failed request or body read: a timeout or no connection explain themselves
+    fn request_error(&self, e: reqwest::Error) -> anyhow::Error {
+        if e.is_timeout() {
+            RequestTimedOut { after: self.options.timeout }.into()
+        } else if e.is_connect() {
+            ConnectionFailed { source: e }.into()
+        } else {
+            e.into()
+        }
+    }
+
+    /// Requests made so far by this client and every client sharing its budget
+    fn metrics(&self) -> RequestMetrics {
+        *self.metrics.lock().unwrap()
+    }
+
+    fn get_gh_token(hostname: Option<&str>) -> Result<String> {
+        let mut command = std::process::Command::new("gh");
+        command.args(["auth", "token"]);
+        if let Some(hostname) = hostname {
+            command.args(["--hostname", hostname]);
+        }
+        let output = command
+            .output()
+            .context("Failed to run 'gh auth token' command")?;
+        
+        if !output.status.success() {
+            anyhow::bail!("gh CLI not authenticated. Run 'gh auth login' first.");
+        }
+        
+        let token = String::from_utf8(output.stdout)?
+            .trim()
+            .to_string();
+            
+        if token.is_empty() {
+            anyhow::bail!("No token returned from gh CLI");
+        }
+        
+        Ok(token)
+    }
+
+    async fn get_user(&self) -> Result<User> {
+        let url = self.options.endpoints.rest(&format!("/users/{}", self.username));
+        let response = self.send(self.client.get(&url)).await?;
+        check_user_response(&self.username, response.status())?;
+        
+        let user: User = response.json().await.map_err(|e| self.request_error(e))?;
+        if user.account_type == "Organization" {
+            anyhow::bail!("{} is an organization; use --org {} to list its repositories", user.login, user.login);
+        }
+        if let (Some(window), Some(created_at)) = (self.options.window, user.created_at) {
+            let joined = created_at.with_timezone(&self.options.timezone).date_naive();
+            if window.to < joined {
+                anyhow::bail!("{} joined GitHub on {}, after the requested window ends ({})", user.login, joined, window.to);
+            }
+        }
+        Ok(user)
+    }
+
+    
+    async fn get_data_from_graphql(&self) -> Result<(ContributionGraph, Vec<RepositoryWithCommits>, bool, Option<ContributionBreakdown>, Option<Punchcard>)> {
+        let query = r#" 
+        query($username: String!, $repoCount: Int!, $from: DateTime, $to: DateTime) {
+            viewer {
+                login
+            }
+            user(login: $username) {
+                # --graphql-extra fields
+                contributionsCollection(from: $from, to: $to) {
+                    totalCommitContributions
+                    totalPullRequestContributions
+                    totalIssueContributions
+                    totalPullRequestReviewContributions
+                    restrictedContributionsCount
+                    contributionCalendar {
+                        totalContributions
+                        weeks {
+                            contributionDays {
+                                date
+                                contributionCount
+                                contributionLevel
+                            }
+                        }
+                    }
+                }
+                repositories(
+                    first: $repoCount
+                    orderBy: {field: PUSHED_AT, direction: DESC}
+                    affiliations: [OWNER, COLLABORATOR, ORGANIZATION_MEMBER]
+                    ownerAffiliations: [OWNER, COLLABORATOR, ORGANIZATION_MEMBER]
+                ) {
+                    nodes {
+                        name
+                        pushedAt
+                        isPrivate
+                        isFork
+                        parent {
+                            nameWithOwner
+                        }
+                        owner {
+                            login
+                        }
+                        primaryLanguage {
+                            name
+                        }
+                    }
+                }
+            }
+        }
+        "#;
+        
+        // Leaving from/to null keeps GitHub's own trailing-year calendar
+        let window = self.options.window;
+        let variables = serde_json::json!({
+            "username": self.username,
+            // A filter would leave few of the newest repositories, so look further back to fill the list
+            "repoCount": if self.options.repo_filter.is_some() || self.options.language.is_some() { FILTERED_REPO_FETCH } else { self.options.repo_count },
+            "from": window.map(|w| w.start_timestamp(self.options.timezone)),
+            "to": window.map(|w| w.end_timestamp(self.options.timezone)),
+        });
+        
+        let mut graphql_response = self.query_calendar(query, variables.clone()).await?;
+
+        // GitHub occasionally answers with a total but a missing or cut-off weeks array; one retry usually fixes it
+        let min_weeks = self.options.window.map_or(MIN_PLAUSIBLE_WEEKS, |window| window.expected_weeks());
+        let truncated = graphql_response.data.user.as_ref()
+            .is_some_and(|user| calendar_looks_truncated(&user.contributions_collection.contribution_calendar, min_weeks));
+        if truncated {
+            if self.options.verbose {
+                eprintln!("{}", "Calendar came back with contributions but too few weeks, retrying once".bright_black());
+            }
+            graphql_response = self.query_calendar(query, variables).await?;
+        }
+
+        let viewer_is_user = graphql_response.data.viewer
+            .is_some_and(|viewer| viewer.login.eq_ignore_ascii_case(&self.username));
+        let user_data = require_user(&self.username, graphql_response.data.user)?;
+        let calendar = user_data.contributions_collection.contribution_calendar;
+        // Subtracting private contributions leaves no way to tell which kinds they were
+        let breakdown = Some(user_data.contributions_collection.breakdown)
+            .filter(|_| !(self.options.public_only && viewer_is_user));
+        
+        // Convert GraphQL data to our format
+        let mut weeks = Vec::new();
+        
+        for graphql_week in calendar.weeks {
+            let mut week = Week { days: Vec::new() };
+            
+            for graphql_day in graphql_week.contribution_days {
+                let level = CONTRIBUTION_LEVELS.iter()
+                    .position(|&name| name == graphql_day.contribution_level)
+                    .unwrap_or(0) as u8;
+                
+                week.days.push(Day {
+                    date: graphql_day.date,
+                    count: graphql_day.contribution_count,
+                    level,
+                });
+            }
+            
+            weeks.push(week);
+        }
+        
+        let mut contribution_graph = ContributionGraph {
+            weeks,
+            total_contributions: calendar.total_contributions,
+        };
+        if let Some(window) = self.options.window {
+            if contribution_graph.retain_window(window) && self.options.verbose {
+                eprintln!("{}", "Dropped calendar days outside the requested window".bright_black());
+            }
+        }
+
+        if self.options.public_only && viewer_is_user {
+            self.subtract_private_contributions(&mut contribution_graph).await?;
+        }
+
+        let (repos_with_commits, punchcard) = self.count_repository_commits(user_data.repositories.nodes).await?;
+
+        Ok((contribution_graph, repos_with_commits, viewer_is_user, breakdown, punchcard))
+    }
+
+    /// The recent-repositories table: `nodes` (newest push first) narrowed by the filters and
+    /// `--repos`, with this month's commits counted in the first `--scan-repos` of them
+    async fn count_repository_commits(&self, nodes: Vec<Repository>) -> Result<(Vec<RepositoryWithCommits>, Option<Punchcard>)> {
+        // Get this month's commits for the first `scan_repos` repositories (single API call per repo)
+        // Filtering before counting means skipped repositories cost no API calls
+        let repos: Vec<Repository> = nodes.into_iter()
+            .filter(|repo| {
+                let full_name = format!("{}/{}", repo.owner.login, repo.name);
+                self.options.repo_filter.as_ref().is_none_or(|filter| filter.is_match(&full_name))
+                    && self.options.language.as_ref().is_none_or(|language| {
+                        repo.primary_language.as_ref().is_some_and(|primary| primary.name.eq_ignore_ascii_case(language))
+                    })
+            })
+            .take(self.options.repo_count)
+            .collect();
+        let full_names: Vec<String> = repos.iter()
+            .map(|repo| format!("{}/{}", repo.owner.login, repo.name))
+            .collect();
+        let scan_count = self.options.scan_repos.min(full_names.len());
+        let mut results = self.fetch_month_commits(&full_names[..scan_count]).await.into_iter();
+
+        let mut fetched = Vec::new();
+        let mut errors = Vec::new();
+        for (repo, full_name) in repos.iter().zip(full_names) {
+            // One repository failing shouldn't take the others down, but its zeros aren't real
+            let (commits, error) = if let Some(result) = results.next() {
+                match result {
+                    Ok(commits) => (commits, None),
+                    // The other repositories would run into the same limit
+                    Err(e) if e.is::<RateLimited>() || e.is::<RetryBudgetExhausted>() => return Err(e),
+                    Err(e) if self.options.strict => {
+                        return Err(e.context(format!("Failed to count commits in {}", full_name)));
+                    }
+                    Err(e) => (Vec::new(), Some(e.to_string())),
+                }
+            } else {
+                (Vec::new(), None)
+            };
+            let parent = repo.parent.as_ref()
+                .filter(|_| repo.is_fork)
+                .map(|parent| parent.name_with_owner.clone());
+            fetched.push(FetchedCommits { full_name, parent, commits });
+            errors.push(error);
+        }
+
+        if self.options.dedupe_forks {
+            for (fork, removed) in dedupe_fork_commits(&mut fetched) {
+                if self.options.verbose {
+                    eprintln!("{}", format!("Not counting {} commits in {} that its upstream already has", removed, fork).bright_black());
+                }
+            }
+        }
+        let punchcard = self.options.punchcard
+            .then(|| Punchcard::from_commits(fetched.iter().flat_map(|repo| &repo.commits), self.options.timezone));
+
+        let mut repos_with_commits = Vec::new();
+        for (index, ((repo, fetched), error)) in repos.into_iter().zip(fetched).zip(errors).enumerate() {
+            let scanned = index < self.options.scan_repos;
+            let full_name = fetched.full_name;
+            let counts = if scanned {
+                self.count_recent_commits(&full_name, &fetched.commits)
+            } else {
+                CommitCounts::default()
+            };
+            repos_with_commits.push(RepositoryWithCommits {
+                name: repo.name,
+                full_name: full_name.clone(),
+                pushed_at: repo.pushed_at,
+                is_private: repo.is_private,
+                scanned,
+                today_commits: counts.today,
+                week_commits: counts.week,
+                month_commits: counts.month,
+                unparseable_dates: counts.unparseable_dates,
+                error,
+                commits: counts.commits,
+            });
+        }
+
+        Ok((repos_with_commits, punchcard))
+    }
+
+    async fn query_calendar(&self, query: &str, variables: serde_json::Value) -> Result<GraphQLResponse> {
+        match &self.options.graphql_extra {
+            Some(extra) => self.query_with_extra(query, variables, extra).await,
+            None => self.post_graphql(query, variables).await,
+        }
+    }
+
+    /// Run the calendar query with the `--graphql-extra` fields spliced in and save the raw `user`
+    /// object for them. The core fields are decoded on their own, and if GitHub rejects the
+    /// extra fields the plain query is retried so a bad fragment can't cost the graph.
+    async fn query_with_extra(&self, query: &str, variables: serde_json::Value, extra: &GraphqlExtra) -> Result<GraphQLResponse> {
+        let extended = query.replace(GRAPHQL_EXTRA_MARKER, &extra.fields);
+        match self.post_graphql::<serde_json::Value>(&extended, variables.clone()).await {
+            Ok(raw) => {
+                std::fs::write(&extra.output, serde_json::to_string_pretty(&raw["data"]["user"])?)
+                    .with_context(|| format!("Failed to write --graphql-extra output to {}", extra.output.display()))?;
+                return Ok(serde_json::from_value(raw)?);
+            }
+            Err(e) if e.is::<GraphQLFailure>() => {
+                let message = e.downcast_ref::<GraphQLFailure>().map_or("unknown error", |failure| failure.message.as_str());
+                eprintln!("{}", format!("--graphql-extra fields were rejected ({}), continuing without them", message).bright_yellow());
+            }
+            Err(e) if e.is::<ServiceUnavailable>()
+                || e.is::<ConnectionFailed>()
+                || e.is::<RequestTimedOut>()
+                || e.is::<RetryBudgetExhausted>()
+                || e.is::<RateLimited>() => return Err(e),
+            Err(e) => {
+                eprintln!("{}", format!("--graphql-extra query failed ({}), continuing without it", e).bright_yellow());
+            }
+        }
+
+        self.post_graphql(query, variables).await
+    }
+
+    /// Remove contributions to private repositories from the calendar, day by day.
+    /// GitHub has no switch to leave them out of `contributionCalendar`, so they're looked up
+    /// per repository and subtracted. A repository's contributions come 100 at a time; the
+    /// later pages are asked for again per kind, with the cursor, up to `--max-pages`.
+    async fn subtract_private_contributions(&self, graph: &mut ContributionGraph) -> Result<()> {
+        let window = self.options.window;
+        let variables = serde_json::json!({
+            "username": self.username,
+            "from": window.map(|w| w.start_timestamp(self.options.timezone)),
+            "to": window.map(|w| w.end_timestamp(self.options.timezone)),
+        });
+        let response: serde_json::Value = self.post_graphql(&private_contributions_query(&PRIVATE_CONTRIBUTION_KINDS, false), variables.clone()).await?;
+        let collection = &response["data"]["user"]["contributionsCollection"];
+
+        let mut private_by_date: HashMap<NaiveDate, u32> = HashMap::new();
+        // What couldn't be looked up and is still in the calendar
+        let mut incomplete = Vec::new();
+        for kind in PRIVATE_CONTRIBUTION_KINDS {
+            let repositories = collection[kind.field].as_array().map(Vec::as_slice).unwrap_or_default();
+            if repositories.len() >= MAX_CONTRIBUTION_REPOSITORIES {
+                incomplete.push(format!("{} beyond the first {} repositories", kind.label, MAX_CONTRIBUTION_REPOSITORIES));
+            }
+            for entry in repositories.iter().filter(|entry| entry["repository"]["isPrivate"] == true) {
+                let name = entry["repository"]["nameWithOwner"].as_str().unwrap_or_default();
+                let total = entry["contributions"]["totalCount"].as_u64().unwrap_or(0);
+                let mut connection = entry["contributions"].clone();
+                let mut fetched = 0;
+                let mut page = 1;
+                loop {
+                    for node in connection["nodes"].as_array().map(Vec::as_slice).unwrap_or_default() {
+                        fetched += 1;
+                        let Some(date) = node["occurredAt"].as_str()
+                            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                            .map(|d| d.with_timezone(&self.options.timezone).date_naive())
+                        else {
+                            continue;
+                        };
+                        // Commit contributions are grouped per day and carry a count, the rest are single
+                        let count = node["commitCount"].as_u64().unwrap_or(1) as u32;
+                        *private_by_date.entry(date).or_default() += count;
+                    }
+                    let cursor = connection["pageInfo"]["endCursor"].as_str().map(str::to_string);
+                    let (true, Some(cursor)) = (connection["pageInfo"]["hasNextPage"] == true, cursor) else {
+                        break;
+                    };
+                    if page >= self.options.max_pages {
+                        break;
+                    }
+                    // The cursor goes to every listed repository's connection; only this one's answer is read
+                    let mut variables = variables.clone();
+                    variables["after"] = cursor.into();
+                    let response: serde_json::Value = self.post_graphql(&private_contributions_query(&[kind], true), variables).await?;
+                    let next = response["data"]["user"]["contributionsCollection"][kind.field].as_array()
+                        .and_then(|repositories| repositories.iter().find(|entry| entry["repository"]["nameWithOwner"] == name));
+                    let Some(next) = next else {
+                        break;
+                    };
+                    connection = next["contributions"].clone();
+                    page += 1;
+                }
+                if fetched < total {
+                    incomplete.push(format!("{} of {} {} in {}", total - fetched, total, kind.label, name));
+                }
+            }
+        }
+        if !incomplete.is_empty() {
+            let message = format!("--public-only couldn't look up every private contribution, so these are still counted: {}", incomplete.join(", "));
+            if self.options.strict {
+                anyhow::bail!(message);
+            }
+            eprintln!("{}", message.bright_yellow());
+        }
+
+        for day in graph.weeks.iter_mut().flat_map(|week| week.days.iter_mut()) {
+            if let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") {
+                day.count = day.count.saturating_sub(private_by_date.get(&date).copied().unwrap_or(0));
+            }
+        }
+
+        // Levels are quartiles of the remaining counts, like GitHub computes them
+        let counts: Vec<u32> = graph.weeks.iter().flat_map(|week| week.days.iter().map(|day| day.count)).collect();
+        let thresholds = level_thresholds(&counts, &DEFAULT_QUANTILES);
+        for day in graph.weeks.iter_mut().flat_map(|week| week.days.iter_mut()) {
+            day.level = level_for_count(day.count, &thresholds);
+        }
+        graph.total_contributions = counts.iter().sum();
+
+        Ok(())
+    }
+
+    /// POST a GraphQL query, retrying with backoff while GitHub times out or returns server errors
+    async fn post_graphql<T: serde::de::DeserializeOwned>(&self, query: &str, variables: serde_json::Value) -> Result<T> {
+        let request_body = serde_json::json!({
+            "query": query,
+            "variables": variables
+        });
+        
+        let response = self.send_with_retries(
+            self.client
+                .post(&self.options.endpoints.graphql)
+                .json(&request_body)
+        ).await?;
+            
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            anyhow::bail!("GitHub rejected the token (HTTP 401); check --token, or run 'gh auth login' again");
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("GraphQL request failed: {}", response.status());
+        }
+
+        let headers = response.headers().clone();
+        let body: serde_json::Value = response.json().await.map_err(|e| if e.is_timeout() {
+            self.request_error(e)
+        } else {
+            anyhow::Error::new(e).context("GitHub's GraphQL response isn't valid JSON")
+        })?;
+        check_graphql_errors(&body, &headers, self.options.timezone)?;
+        serde_json::from_value(body).context("GitHub's GraphQL response doesn't have the expected fields")
+    }
+
+    /// Logins of an organization's members, paginating up to `ORG_MEMBERS_LIMIT`. Without
+    /// the `read:org` scope (or membership) GitHub only returns the public members.
+    async fn get_org_members(&self, org: &str) -> Result<Vec<String>> {
+        let query = r#"
+        query($org: String!, $after: String) {
+            organization(login: $org) {
+                membersWithRole(first: 100, after: $after) {
+                    nodes {
+                        login
+                    }
+                    pageInfo {
+                        hasNextPage
+                        endCursor
+                    }
+                }
+            }
+        }
+        "#;
+
+        let mut members = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let variables = serde_json::json!({ "org": org, "after": cursor });
+            let response: OrgMembersResponse = self.post_graphql(query, variables).await?;
+            let Some(organization) = response.data.organization else {
+                anyhow::bail!("Organization '{}' not found", org);
+            };
+
+            let connection = organization.members_with_role;
+            members.extend(connection.nodes.into_iter().map(|member| member.login));
+            if members.len() >= ORG_MEMBERS_LIMIT {
+                eprintln!("{}", format!("Only ranking the first {} members of {}", ORG_MEMBERS_LIMIT, org).bright_yellow());
+                members.truncate(ORG_MEMBERS_LIMIT);
+                break;
+            }
+            match connection.page_info.end_cursor {
+                Some(end_cursor) if connection.page_info.has_next_page => cursor = Some(end_cursor),
+                _ => break,
+            }
+        }
+
+        Ok(members)
+    }
+
+    /// An organization's recently pushed repositories with everyone's commits counted, the
+    /// recent-repositories table for an organization; there's no calendar for organizations
+    async fn get_org_repos(&self) -> Result<Vec<RepositoryWithCommits>> {
+        let query = r#"
+        query($org: String!, $repoCount: Int!) {
+            organization(login: $org) {
+                repositories(first: $repoCount, orderBy: {field: PUSHED_AT, direction: DESC}) {
+                    nodes {
+                        name
+                        pushedAt
+                        isPrivate
+                        isFork
+                        parent {
+                            nameWithOwner
+                        }
+                        owner {
+                            login
+                        }
+                        primaryLanguage {
+                            name
+                        }
+                    }
+                }
+            }
+        }
+        "#;
+        let variables = serde_json::json!({
+            "org": self.username,
+            "repoCount": if self.options.repo_filter.is_some() || self.options.language.is_some() { FILTERED_REPO_FETCH } else { self.options.repo_count },
+        });
+        let response: OrgReposResponse = self.post_graphql(query, variables).await?;
+        let organization = require_user(&self.username, response.data.organization)?;
+        let (repos, _) = self.count_repository_commits(organization.repositories.nodes).await?;
+        Ok(repos)
+    }
+
+    /// The user's repositories with the most stars, paginating until `count` are collected
+    async fn get_top_repos(&self, count: usize) -> Result<Vec<TopRepository>> {
+        let query = r#"
+        query($username: String!, $first: Int!, $after: String) {
+            user(login: $username) {
+                repositories(
+                    first: $first
+                    after: $after
+                    ownerAffiliations: [OWNER]
+                    orderBy: {field: STARGAZERS, direction: DESC}
+                ) {
+                    nodes {
+                        nameWithOwner
+                        stargazerCount
+                        isPrivate
+                        pushedAt
+                        primaryLanguage {
+                            name
+                        }
+                    }
+                    pageInfo {
+                        hasNextPage
+                        endCursor
+                    }
+                }
+            }
+        }
+        "#;
+
+        let mut repos = Vec::new();
+        let mut cursor: Option<String> = None;
+        while repos.len() < count {
+            let variables = serde_json::json!({
+                "username": self.username,
+                "first": (count - repos.len()).min(100),
+                "after": cursor
+            });
+            let response: TopReposResponse = self.post_graphql(query, variables).await?;
+            let user = require_user(&self.username, response.data.user)?;
+
+            repos.extend(user.repositories.nodes);
+            match user.repositories.page_info.end_cursor {
+                Some(end_cursor) if user.repositories.page_info.has_next_page => cursor = Some(end_cursor),
+                _ => break,
+            }
+        }
+
+        repos.truncate(count);
+        Ok(repos)
+    }
+
+    /// `get_month_commits` for several repositories, a few at a time, with the results in the order given
+    async fn fetch_month_commits(&self, full_names: &[String]) -> Vec<Result<Vec<serde_json::Value>>> {
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(REPO_SCAN_CONCURRENCY));
+        let mut tasks = tokio::task::JoinSet::new();
+
+        for (index, full_name) in full_names.iter().enumerate() {
+            let client = self.for_user(self.username.clone());
+            let full_name = full_name.clone();
+            let semaphore = Arc::clone(&semaphore);
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                (index, client.get_month_commits(&full_name).await)
+            });
+        }
+
+        let mut results: Vec<Option<Result<Vec<serde_json::Value>>>> = full_names.iter().map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, result)) = joined {
+                results[index] = Some(result);
+            }
+        }
+        // A task that panicked left its slot empty; the other repositories keep their place
+        results.into_iter()
+            .zip(full_names)
+            .map(|(result, full_name)| result.unwrap_or_else(|| Err(anyhow::anyhow!("Counting commits in {} stopped unexpectedly", full_name))))
+            .collect()
+    }
+
+    /// Fetch the user's commits in a repository since the 1st of this month
+    /// Uses the same time period calculations as the main stats to ensure consistency
+    async fn get_month_commits(&self, full_repo_name: &str) -> Result<Vec<serde_json::Value>> {
+        // Use the --timezone zone (same as main stats) to ensure consistency
+        let zone = self.options.timezone;
+        let today = today_in(zone);
+        let today_end = local_day_end(&zone, today);
+
+        // This month: 1st day of current month to now (same calculation as main stats)
+        let month_start = local_day_start(&zone, first_of_month(today));
+
+        // Fetch all commits for the month period in a single API call for efficiency
+        let month_start_str = month_start.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let today_end_str = today_end.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        self.get_commits_with_dates(full_repo_name, &month_start_str, &today_end_str).await
+    }
+
+    /// Get commit counts for today, this week, and this month from a repository's month of commits
+    fn count_recent_commits(&self, full_repo_name: &str, commits: &[serde_json::Value]) -> CommitCounts {
+        let zone = self.options.timezone;
+        let today = today_in(zone);
+        let today_start = local_day_start(&zone, today);
+        let today_end = local_day_end(&zone, today);
+
+        // This week: first day of the current week to now (same calculation as main stats)
+        let week_start = local_day_start(&zone, self.options.week_start.week_of(today));
+
+        // Count commits by filtering in memory (more efficient than separate API calls)
+        let mut counts = count_commits_by_period(commits, today_start, today_end, week_start, |sha, date| {
+            if self.options.verbose {
+                eprintln!("{}", format!("Skipping commit {} in {}: unparseable date {:?}", sha, full_repo_name, date).bright_black());
+            }
+        });
+        if self.options.list_commits {
+            counts.commits = summarize_commits(commits, LIST_COMMITS_LIMIT, zone);
+        }
+
+        counts
+    }
+
+    /// Fetch commits from GitHub API with pagination, filtered by author
+    async fn get_commits_with_dates(&self, full_repo_name: &str, since: &str, until: &str) -> Result<Vec<serde_json::Value>> {
+        let mut all_commits = Vec::new();
+        let mut page = 1;
+        let per_page = 100;
+
+        loop {
+            let url = self.options.endpoints.rest(&format!(
+                "/repos/{}/commits?since={}&until={}&page={}&per_page={}",
+                full_repo_name, since, until, page, per_page
+            ));
+
+            // Conditional request: a 304 reuses the cached page and doesn't count against the rate limit
+            let cached = cache::load_response(&url);
+            let mut request = self.client.get(&url);
+            if let Some(cached) = &cached {
+                request = request.header(reqwest::header::IF_NONE_MATCH, &cached.etag);
+            }
+
+            match self.send_with_retries(request).await {
+                Ok(response) => {
+                    let next_page = link_has_next(response.headers());
+                    let body = if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                        cached.map(|cached| cached.body)
+                    } else if response.status() == reqwest::StatusCode::CONFLICT {
+                        // GitHub answers 409 for a repository without any commits
+                        None
+                    } else if response.status().is_success() {
+                        let etag = response.headers()
+                            .get(reqwest::header::ETAG)
+                            .and_then(|etag| etag.to_str().ok())
+                            .map(str::to_string);
+                        let body = response.text().await.map_err(|e| self.request_error(e))?;
+                        if let Some(etag) = etag {
+                            // Caching is best-effort, a failed write only costs a full request next time
+                            cache::store_response(&url, &etag, &body).ok();
+                        }
+                        Some(body)
+                    } else {
+                        anyhow::bail!("Fetching commits for {} failed: HTTP {}", full_repo_name, response.status());
+                    };
+
+                    if let Some(body) = body {
+                        let commits: Vec<serde_json::Value> = serde_json::from_str(&body)?;
+                        if commits.is_empty() {
+                            break;
+                        }
+                        // Without a Link header (a 304 may not repeat it), only a full page can have a successor
+                        let more = next_page.unwrap_or(commits.len() == per_page);
+
+                        // Filter commits by GitHub author (not Git author) for accuracy
+                        let user_commits: Vec<serde_json::Value> = commits.into_iter().filter(|commit| {
+                            if self.options.all_authors {
+                                return true;
+                            }
+                            if let Some(author) = commit.get("author") {
+                                if let Some(login) = author.get("login") {
+                                    return login.as_str() == Some(&self.username);
+                                }
+                            }
+                            false
+                        }).collect();
+
+                        all_commits.extend(user_commits);
+                        if !more {
+                            break;
+                        }
+                        if page >= self.options.max_pages {
+                            eprintln!("{}", format!(
+                                "Stopped counting commits in {} after {} pages of {} (--max-pages), so its counts are too low",
+                                full_repo_name, page, per_page
+                            ).bright_yellow());
+                            break;
+                        }
+                        page += 1;
+                    } else {
+                        break;
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(all_commits)
+    }
+    
+    async fn generate_data(&self) -> Result<(ContributionGraph, Vec<RepositoryWithCommits>, bool, Option<ContributionBreakdown>, Option<Punchcard>)> {
+        match self.get_data_from_graphql().await {
+            Ok(data) => Ok(data),
+            // An outage, a missing account or no network is not an empty graph, so let it reach the user;
+            // these explain themselves, everything else says what it was fetching
+            Err(e) if e.is::<ServiceUnavailable>()
+                || e.is::<AccountNotFound>()
+                || e.is::<ConnectionFailed>()
+                || e.is::<RequestTimedOut>()
+                || e.is::<RetryBudgetExhausted>()
+                || e.is::<RateLimited>()
+                || e.is::<GraphQLFailure>() => Err(e),
+            Err(e) => Err(e.context("Failed to fetch the contribution calendar")),
+        }
+    }
+
+
+    /// Build stats for a single repository from the user's commit dates over the past year
+    async fn get_repo_stats(&self, full_repo_name: &str, quantiles: &[f64]) -> Result<Stats> {
+        let user = self.get_user().await?;
+
+        let url = self.options.endpoints.rest(&format!("/repos/{}", full_repo_name));
+        let response = self.send(self.client.get(&url)).await?;
+        if response.status() == 404 {
+            anyhow::bail!("Repository '{}' not found", full_repo_name);
+        }
+
+        // Same trailing window as GitHub's calendar unless a narrower one was asked for
+        let window = self.options.window.unwrap_or_else(|| DateWindow::trailing_year(self.options.timezone));
+
+        let commits = self.get_commits_with_dates(
+            full_repo_name,
+            &window.start_timestamp(self.options.timezone),
+            &window.commits_until(self.options.timezone),
+        ).await?;
+
+        let dates = commit_dates(&commits, self.options.timezone);
+
+        Ok(Stats {
+            username: user.login,
+            viewer_is_user: false,
+            private_excluded: false,
+            limited_history: false,
+            contribution_graph: build_graph_from_dates(&dates, window.from, window.to, quantiles),
+            recent_repos: Vec::new(),
+            pinned_repos: Vec::new(),
+            breakdown: None,
+            most_active_weekday: None,
+            punchcard: None,
+        })
+    }
+
+    /// Build the graph from the user's commits to every repository they committed to in the window.
+    /// The repositories come from GraphQL (at most 100), the commits from the REST commits endpoint,
+    /// so this costs one request per 100 commits of every repository instead of a single query.
+    async fn get_graph_from_commits(&self, quantiles: &[f64]) -> Result<ContributionGraph> {
+        let query = r#"
+        query($username: String!, $from: DateTime, $to: DateTime) {
+            user(login: $username) {
+                contributionsCollection(from: $from, to: $to) {
+                    commitContributionsByRepository(maxRepositories: 100) {
+                        repository { nameWithOwner isPrivate }
+                    }
+                }
+            }
+        }
+        "#;
+
+        let window = self.options.window.unwrap_or_else(|| DateWindow::trailing_year(self.options.timezone));
+        let since = window.start_timestamp(self.options.timezone);
+        let until = window.commits_until(self.options.timezone);
+        let variables = serde_json::json!({
+            "username": self.username,
+            "from": since,
+            "to": window.end_timestamp(self.options.timezone),
+        });
+        let response: serde_json::Value = self.post_graphql(query, variables).await?;
+        let repositories = response["data"]["user"]["contributionsCollection"]["commitContributionsByRepository"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let mut dates = Vec::new();
+        for entry in repositories {
+            if self.options.public_only && entry["repository"]["isPrivate"] == true {
+                continue;
+            }
+            let Some(name) = entry["repository"]["nameWithOwner"].as_str() else {
+                continue;
+            };
+            let commits = match self.get_commits_with_dates(name, &since, &until).await {
+                Ok(commits) => commits,
+                // The other repositories would run into the same limit
+                Err(e) if self.options.strict || e.is::<RetryBudgetExhausted>() || e.is::<RateLimited>() => return Err(e),
+                Err(e) => {
+                    eprintln!("{}", format!("Skipping {}: {:#}", name, e).bright_yellow());
+                    continue;
+                }
+            };
+            if self.options.verbose {
+                eprintln!("{}", format!("Counted {} commits in {}", commits.len(), name).bright_black());
+            }
+            dates.extend(commit_dates(&commits, self.options.timezone));
+        }
+
+        Ok(build_graph_from_dates(&dates, window.from, window.to, quantiles))
+    }
+
+    /// Approximate the calendar from the public events feed, which works without a token
+    /// but only reaches back about 90 days (and at most 300 events)
+    async fn get_graph_from_events(&self) -> Result<ContributionGraph> {
+        let window = self.options.window.unwrap_or_else(|| DateWindow::trailing_year(self.options.timezone));
+        let history_start = (window.to - chrono::Duration::days(EVENTS_HISTORY_DAYS)).max(window.from);
+
+        let mut dates = Vec::new();
+        for page in 1..=EVENTS_MAX_PAGES {
+            let url = self.options.endpoints.rest(&format!("/users/{}/events?per_page=100&page={}", self.username, page));
+            let response = self.send(self.client.get(&url)).await?;
+            if !response.status().is_success() {
+                if self.options.strict {
+                    anyhow::bail!("Fetching public events failed: HTTP {}", response.status());
+                }
+                break;
+            }
+
+            let events: Vec<serde_json::Value> = response.json().await.map_err(|e| self.request_error(e))?;
+            if events.is_empty() {
+                break;
+            }
+
+            for event in &events {
+                let Some(date) = event.get("created_at")
+                    .and_then(|d| d.as_str())
+                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                    .map(|d| d.with_timezone(&self.options.timezone).date_naive())
+                else {
+                    continue;
+                };
+                if date < history_start {
+                    continue;
+                }
+
+                // A push counts once per commit, like the calendar does; other events count once
+                let weight = match event.get("type").and_then(|t| t.as_str()) {
+                    Some("PushEvent") => event.get("payload")
+                        .and_then(|p| p.get("size"))
+                        .and_then(|s| s.as_u64())
+                        .unwrap_or(1),
+                    Some("PullRequestEvent" | "IssuesEvent" | "PullRequestReviewEvent" | "CreateEvent") => 1,
+                    _ => 0,
+                };
+                dates.extend(std::iter::repeat_n(date, weight as usize));
+            }
+        }
+
+        Ok(build_graph_from_dates(&dates, window.from, window.to, &DEFAULT_QUANTILES))
+    }
+
+    /// Stats for the whole calendar, or for one repository's commits with `--repo`
+    async fn fetch_stats(&self, repo: Option<&str>, quantiles: &[f64]) -> Result<Stats> {
+        match repo {
+            Some(repo) => self.get_repo_stats(repo, quantiles).await,
+            None => self.get_stats(quantiles).await,
+        }
+    }
+
+    /// Everything that changes what `fetch_stats` returns, so differently configured runs
+    /// never share a cache entry
+    fn stats_cache_key(&self, repo: Option<&str>, quantiles: &[f64]) -> String {
+        let window = self.options.window
+            .map(|window| format!("{}..{}", window.from, window.to))
+            .unwrap_or_default();
+        format!(
+            "{}|api={}|auth={}|repos={}|scan={}|public_only={}|pinned={}|commits={}|punchcard={}|all_authors={}|dedupe_forks={}|week_start={:?}|timezone={}|filter={}|lang={}|max_pages={}|window={}|repo={}|source={:?}|quantiles={:?}",
+            self.username.to_lowercase(),
+            self.options.endpoints.rest,
+            self.authenticated,
+            self.options.repo_count,
+            self.options.scan_repos,
+            self.options.public_only,
+            self.options.pinned,
+            self.options.list_commits,
+            self.options.punchcard,
+            self.options.all_authors,
+            self.options.dedupe_forks,
+            self.options.week_start,
+            self.options.timezone.name(),
+            self.options.repo_filter.as_ref().map_or("", |filter| filter.as_str()),
+            self.options.language.as_deref().unwrap_or_default().to_lowercase(),
+            self.options.max_pages,
+            window,
+            repo.unwrap_or_default(),
+            self.options.source,
+            quantiles
+        )
+    }
+
+    /// Stats for the whole calendar; `quantiles` only matter when the levels are computed locally
+    async fn get_stats(&self, quantiles: &[f64]) -> Result<Stats> {
+        if self.options.source == GraphSource::Commits && !self.authenticated {
+            anyhow::bail!("--source commits needs a token to find the repositories the user committed to");
+        }
+        let user = self.get_user().await?;
+
+        // The GraphQL calendar requires a token, so without one fall back to public events
+        let (contribution_graph, recent_repos, viewer_is_user, breakdown, punchcard) = if self.authenticated {
+            self.generate_data().await?
+        } else {
+            (self.get_graph_from_events().await?, Vec::new(), false, None, None)
+        };
+        // The calendar query still provides the repository table and whose token this is
+        let (contribution_graph, breakdown) = match self.options.source {
+            GraphSource::Calendar => (contribution_graph, breakdown),
+            // A commits-only graph next to a breakdown of everything would be confusing
+            GraphSource::Commits => (self.get_graph_from_commits(quantiles).await?, None),
+        };
+
+        // Pinned items are GraphQL-only too; they're extra context, so a failure only costs the section
+        let pinned_repos = if self.options.pinned && self.authenticated && self.options.strict {
+            self.get_pinned_repos().await.context("Failed to fetch pinned repositories")?
+        } else if self.options.pinned && self.authenticated {
+            self.get_pinned_repos().await.unwrap_or_else(|e| {
+                eprintln!("{}", format!("Couldn't fetch pinned repositories: {}", e).bright_yellow());
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        Ok(Stats {
+            username: user.login,
+            viewer_is_user,
+            private_excluded: self.options.public_only && viewer_is_user,
+            limited_history: !self.authenticated,
+            contribution_graph,
+            recent_repos,
+            pinned_repos,
+            breakdown,
+            most_active_weekday: None,
+            punchcard,
+        })
+    }
+
+    /// Today's contribution count from a one-day calendar query, the cheapest request there is.
+    /// Without a username it asks about the token's own account, saving the login lookup.
+    async fn get_today_count(&self) -> Result<u32> {
+        if !self.authenticated {
+            anyhow::bail!("--peek reads the contribution calendar, which needs a token");
+        }
+        // GraphQL rejects declared but unused variables, so $username only exists when it's used
+        let (owner, parameters, selection) = if self.username.is_empty() {
+            ("viewer", "$from: DateTime, $to: DateTime", "viewer")
+        } else {
+            ("user", "$username: String!, $from: DateTime, $to: DateTime", "user(login: $username)")
+        };
+        let query = format!(
+            "query({}) {{ {} {{ contributionsCollection(from: $from, to: $to) {{ contributionCalendar {{ totalContributions }} }} }} }}",
+            parameters,
+            selection
+        );
+
+        let today = today_in(self.options.timezone);
+        let window = DateWindow { from: today, to: today };
+        let mut variables = serde_json::json!({
+            "from": window.start_timestamp(self.options.timezone),
+            "to": window.end_timestamp(self.options.timezone),
+        });
+        if !self.username.is_empty() {
+            variables["username"] = self.username.clone().into();
+        }
+        let response: serde_json::Value = self.post_graphql(&query, variables).await?;
+        if response["data"][owner].is_null() {
+            return Err(AccountNotFound { username: self.username.clone() }.into());
+        }
+        response["data"][owner]["contributionsCollection"]["contributionCalendar"]["totalContributions"]
+            .as_u64()
+            .map(|count| count as u32)
+            .context("GitHub's answer had no contribution count")
+    }
+
+    /// Cache entry of `get_today_count`, which changes with the day
+    fn today_count_cache_key(&self) -> String {
+        format!(
+            "peek|{}|api={}|{}|{}",
+            self.username.to_lowercase(),
+            self.options.endpoints.rest,
+            today_in(self.options.timezone),
+            self.options.timezone.name()
+        )
+    }
+
+    /// The repositories pinned on the user's profile (GitHub allows up to six)
+    async fn get_pinned_repos(&self) -> Result<Vec<PinnedRepository>> {
+        let query = r#"
+        query($username: String!) {
+            user(login: $username) {
+                pinnedItems(first: 6, types: REPOSITORY) {
+                    nodes {
+                        ... on Repository {
+                            nameWithOwner
+                            stargazerCount
+                            description
+                            primaryLanguage {
+                                name
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        "#;
+
+        let variables = serde_json::json!({ "username": self.username });
+        let response: PinnedResponse = self.post_graphql(query, variables).await?;
+        let user = require_user(&self.username, response.data.user)?;
+        Ok(user.pinned_items.nodes)
+    }
+}
+
+/// Author dates in `zone` of commits as returned by the commits API, skipping ones that don't parse
+fn commit_dates(commits: &[serde_json::Value], zone: Tz) -> Vec<NaiveDate> {
+    commits.iter()
+        .filter_map(|commit| commit.get("commit")?.get("author")?.get("date")?.as_str())
+        .filter_map(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.with_timezone(&zone).date_naive())
+        .collect()
+}
+
+/// Commits per hour of each weekday, GitHub's old punch card
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Punchcard {
+    /// Indexed by days from Monday, then by hour, in the `--timezone` zone
+    counts: [[u32; 24]; 7],
+}
+
+impl Punchcard {
+    /// Bucket commits by their author date; commits without a parseable one are left out
+    fn from_commits<'a>(commits: impl IntoIterator<Item = &'a serde_json::Value>, zone: Tz) -> Self {
+        let mut punchcard = Self::default();
+        let dates = commits.into_iter()
+            .filter_map(|commit| commit.get("commit")?.get("author")?.get("date")?.as_str())
+            .filter_map(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.with_timezone(&zone));
+        for date in dates {
+            punchcard.counts[date.weekday().num_days_from_monday() as usize][date.hour() as usize] += 1;
+        }
+        punchcard
+    }
+
+    fn total(&self) -> u32 {
+        self.counts.iter().flatten().sum()
+    }
+}
+
+/// A listed repository's commits for the month, before they're counted
+struct FetchedCommits {
+    full_name: String,
+    /// Owner/name of the upstream, for forks
+    parent: Option<String>,
+    commits: Vec<serde_json::Value>,
+}
+
+/// Remove from each fork the commits whose SHA its upstream, fetched in the same list, also has,
+/// so work pushed to both is only counted upstream. Returns the forks that lost commits, with how many.
+fn dedupe_fork_commits(repos: &mut [FetchedCommits]) -> Vec<(String, usize)> {
+    let sha_of = |commit: &serde_json::Value| commit.get("sha").and_then(|sha| sha.as_str()).map(str::to_string);
+    let shas_by_repo: HashMap<String, HashSet<String>> = repos.iter()
+        .map(|repo| (repo.full_name.to_lowercase(), repo.commits.iter().filter_map(sha_of).collect()))
+        .collect();
+
+    let mut deduped = Vec::new();
+    for repo in repos.iter_mut() {
+        let Some(upstream) = repo.parent.as_ref().and_then(|parent| shas_by_repo.get(&parent.to_lowercase())) else {
+            continue;
+        };
+        let before = repo.commits.len();
+        repo.commits.retain(|commit| sha_of(commit).is_none_or(|sha| !upstream.contains(&sha)));
+        if repo.commits.len() < before {
+            deduped.push((repo.full_name.clone(), before - repo.commits.len()));
+        }
+    }
+    deduped
+}
+
+/// Bucket commits (as returned by the commits API) into today / this week / this month.
+/// The API already limited them to the month, so every commit counts there; one whose
+/// author date doesn't parse can't be placed in a day, is counted as unparseable and
+/// reported through `on_skip` with its SHA and raw date.
+fn count_commits_by_period(
+    commits: &[serde_json::Value],
+    today_start: chrono::DateTime<Utc>,
+    today_end: chrono::DateTime<Utc>,
+    week_start: chrono::DateTime<Utc>,
+    mut on_skip: impl FnMut(&str, &str),
+) -> CommitCounts {
+    let mut counts = CommitCounts {
+        month: commits.len() as u32,
+        ..Default::default()
+    };
+
+    for commit in commits {
+        let raw_date = commit.get("commit")
+            .and_then(|c| c.get("author"))
+            .and_then(|a| a.get("date"))
+            .and_then(|d| d.as_str())
+            .unwrap_or("");
+        let Ok(commit_date) = chrono::DateTime::parse_from_rfc3339(raw_date) else {
+            counts.unparseable_dates += 1;
+            let sha = commit.get("sha").and_then(|s| s.as_str()).unwrap_or("unknown");
+            on_skip(sha, raw_date);
+            continue;
+        };
+        let commit_date = commit_date.with_timezone(&Utc);
+
+        if commit_date >= today_start && commit_date <= today_end {
+            counts.today += 1;
+        }
+        if commit_date >= week_start && commit_date <= today_end {
+            counts.week += 1;
+        }
+    }
+
+    counts
+}
+
+/// A calendar that reports contributions but has fewer weeks than its window needs
+fn calendar_looks_truncated(calendar: &ContributionCalendar, min_weeks: usize) -> bool {
+    calendar.total_contributions > 0 && calendar.weeks.len() < min_weeks
+}
+
+/// Cheap sanity check for a `--graphql-extra` fragment: it must be field selections
+/// (not a whole query) with balanced brackets and closed strings. GitHub does the real validation.
+fn validate_graphql_fragment(fragment: &str) -> Result<()> {
+    let trimmed = fragment.trim();
+    if trimmed.is_empty() {
+        anyhow::bail!("--graphql-extra fragment is empty");
+    }
+    if trimmed.starts_with('{') || ["query", "mutation", "fragment", "subscription"].iter().any(|keyword| trimmed.starts_with(keyword)) {
+        anyhow::bail!("--graphql-extra should list fields to add inside `user {{ ... }}`, not a whole query");
+    }
+
+    let mut open = Vec::new();
+    let mut chars = fragment.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => {
+                // Comment until the end of the line
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '"' => loop {
+                match chars.next() {
+                    Some('\\') => {
+                        chars.next();
+                    }
+                    Some('"') => break,
+                    Some(_) => {}
+                    None => anyhow::bail!("--graphql-extra fragment has an unterminated string"),
+                }
+            },
+            '{' | '(' | '[' => open.push(c),
+            '}' | ')' | ']' => {
+                let expected = match c {
+                    '}' => '{',
+                    ')' => '(',
+                    _ => '[',
+                };
+                if open.pop() != Some(expected) {
+                    anyhow::bail!("--graphql-extra fragment has an unmatched '{}'", c);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(c) = open.pop() {
+        anyhow::bail!("--graphql-extra fragment has an unclosed '{}'", c);
+    }
+    Ok(())
+}
+
+/// Short SHA, date in `zone` and first message line of the first `limit` commits (the API lists newest first)
+fn summarize_commits(commits: &[serde_json::Value], limit: usize, zone: Tz) -> Vec<CommitSummary> {
+    commits.iter()
+        .take(limit)
+        .map(|commit| {
+            let sha = commit.get("sha").and_then(|s| s.as_str()).unwrap_or("unknown");
+            let details = commit.get("commit");
+            let raw_date = details
+                .and_then(|c| c.get("author"))
+                .and_then(|a| a.get("date"))
+                .and_then(|d| d.as_str())
+                .unwrap_or("");
+            let date = chrono::DateTime::parse_from_rfc3339(raw_date)
+                .map(|date| date.with_timezone(&zone).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|_| raw_date.to_string());
+            let message = details
+                .and_then(|c| c.get("message"))
+                .and_then(|m| m.as_str())
+                .and_then(|m| m.lines().next())
+                .unwrap_or("");
+            CommitSummary {
+                sha: sha.chars().take(7).collect(),
+                date,
+                message: message.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn validate_quantiles(quantiles: &[f64]) -> Result<()> {
+    if quantiles.len() != 3 {
+        anyhow::bail!("--quantiles needs exactly 3 boundaries (e.g. 0.25,0.5,0.75), got {}", quantiles.len());
+    }
+    if quantiles.iter().any(|q| !(0.0..=1.0).contains(q)) || quantiles.windows(2).any(|pair| pair[0] >= pair[1]) {
+        anyhow::bail!("--quantiles must be increasing values between 0 and 1");
+    }
+    Ok(())
+}
+
+/// Count at each quantile of the non-zero daily counts, used as the upper bound of levels 1-3
+fn level_thresholds(counts: &[u32], quantiles: &[f64]) -> Vec<u32> {
+    let mut active: Vec<u32> = counts.iter().copied().filter(|&count| count > 0).collect();
+    if active.is_empty() {
+        return Vec::new();
+    }
+    active.sort_unstable();
+
+    quantiles.iter()
+        .map(|q| active[((active.len() - 1) as f64 * q).round() as usize])
+        .collect()
+}
+
+/// Level 0 for no contributions, otherwise 1 plus the number of thresholds the count exceeds
+fn level_for_count(count: u32, thresholds: &[u32]) -> u8 {
+    if count == 0 {
+        return 0;
+    }
+    (1 + thresholds.iter().filter(|&&threshold| count > threshold).count()).min(4) as u8
+}
+
+/// Lay out per-day counts from `dates` in GitHub's week/day shape, from `start` through `end`
+fn build_graph_from_dates(dates: &[NaiveDate], start: NaiveDate, end: NaiveDate, quantiles: &[f64]) -> ContributionGraph {
+    let mut counts_by_date: HashMap<NaiveDate, u32> = HashMap::new();
+    for &date in dates {
+        if date >= start && date <= end {
+            *counts_by_date.entry(date).or_default() += 1;
+        }
+    }
+    build_graph_from_counts(&counts_by_date, start, end, quantiles)
+}
+
+/// Lay out per-day counts in GitHub's week/day shape: weeks run Sunday to Saturday, so the
+/// first one is cut short when `start` isn't a Sunday. Levels are the `quantiles` of the counts.
+fn build_graph_from_counts(counts_by_date: &HashMap<NaiveDate, u32>, start: NaiveDate, end: NaiveDate, quantiles: &[f64]) -> ContributionGraph {
+    let counts: Vec<u32> = counts_by_date.iter()
+        .filter(|(date, _)| (start..=end).contains(*date))
+        .map(|(_, count)| *count)
+        .collect();
+    let thresholds = level_thresholds(&counts, quantiles);
+
+    let mut weeks = Vec::new();
+    let mut week_start = start;
+    while week_start <= end {
+        let days_left_in_week = 7 - week_start.weekday().num_days_from_sunday() as usize;
+        let days: Vec<Day> = week_start.iter_days()
+            .take(days_left_in_week)
+            .take_while(|&date| date <= end)
+            .map(|date| {
+                let count = counts_by_date.get(&date).copied().unwrap_or(0);
+                Day {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    count,
+                    level: level_for_count(count, &thresholds),
+                }
+            })
+            .collect();
+        week_start += chrono::Duration::days(days.len() as i64);
+        weeks.push(Week { days });
+    }
+
+    ContributionGraph {
+        weeks,
+        total_contributions: counts.iter().sum(),
+    }
+}
+
+/// Sum several calendars by date into one graph spanning all their days, with levels
+/// recomputed from the combined counts. Windows that only partly overlap are fine; a
+/// date only some calendars have counts what those have.
+fn merge_graphs(graphs: &[&ContributionGraph], quantiles: &[f64]) -> ContributionGraph {
+    let mut counts_by_date: HashMap<NaiveDate, u32> = HashMap::new();
+    for day in graphs.iter().flat_map(|graph| &graph.weeks).flat_map(|week| &week.days) {
+        if let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") {
+            *counts_by_date.entry(date).or_default() += day.count;
+        }
+    }
+
+    match (counts_by_date.keys().min(), counts_by_date.keys().max()) {
+        (Some(&start), Some(&end)) => build_graph_from_counts(&counts_by_date, start, end, quantiles),
+        _ => ContributionGraph { weeks: Vec::new(), total_contributions: 0 },
+    }
+}
+
+/// Fetch each `--also-user` account with its own token and fold its calendar into `stats`
+/// Cached account stats are reused when younger than `cache_ttl`, or never without one.
+async fn merge_accounts(
+    mut stats: Stats,
+    accounts: &[(String, String)],
+    options: &ClientOptions,
+    cache_ttl: Option<Duration>,
+    quantiles: &[f64],
+) -> Result<Stats> {
+    let mut graphs = Vec::with_capacity(accounts.len());
+    for (username, token) in accounts {
+        let client = GitHubClient::new(username.clone(), vec![token.clone()], options.clone())
+            .context("Failed to create GitHub client")?;
+        let cache_key = client.stats_cache_key(None, &DEFAULT_QUANTILES);
+        let account_stats = match cache_ttl.and_then(|ttl| cache::load_stats::<Stats>(&cache_key, ttl)) {
+            Some(account_stats) => account_stats,
+            None => {
+                let account_stats = client.get_stats(&DEFAULT_QUANTILES).await
+                    .with_context(|| format!("Failed to fetch stats for {}", username))?;
+                cache::store_stats(&cache_key, &account_stats).ok();
+                account_stats
+            }
+        };
+        stats.username = format!("{} + {}", stats.username, account_stats.username);
+        stats.breakdown = stats.breakdown.zip(account_stats.breakdown).map(|(ours, theirs)| ours.add(theirs));
+        graphs.push(account_stats.contribution_graph);
+    }
+
+    let all: Vec<&ContributionGraph> = std::iter::once(&stats.contribution_graph).chain(&graphs).collect();
+    stats.contribution_graph = merge_graphs(&all, quantiles);
+    Ok(stats)
+}
+
+/// Post-fetch adjustments from the command line, for the first fetch and `--watch` refreshes alike
+fn prepare_stats(stats: &mut Stats, normalize: bool, sort: RepoSort, weekdays_only: bool, timezone: Tz) {
+    if normalize {
+        normalize_levels(&mut stats.contribution_graph);
+    }
+    sort_repos(&mut stats.recent_repos, sort);
+    stats.most_active_weekday = most_active_weekday(&stats.contribution_graph, weekdays_only, timezone);
+}
+
+/// Everything `--watch` needs to fetch the stats again the way the first fetch did
+struct Watch<'a> {
+    every: Duration,
+    client: &'a GitHubClient,
+    repo: Option<&'a str>,
+    quantiles: &'a [f64],
+    cache_key: &'a str,
+    also_accounts: &'a [(String, String)],
+    also_options: &'a ClientOptions,
+    normalize: bool,
+    sort: RepoSort,
+    weekdays_only: bool,
+}
+
+impl Watch<'_> {
+    /// Fresh stats, skipping the cache since a cached copy would show the same numbers again
+    async fn refresh(&self) -> Result<Stats> {
+        let mut stats = self.client.fetch_stats(self.repo, self.quantiles).await?;
+        cache::store_stats(self.cache_key, &stats).ok();
+        if !self.also_accounts.is_empty() {
+            stats = merge_accounts(stats, self.also_accounts, self.also_options, None, self.quantiles).await?;
+        }
+        prepare_stats(&mut stats, self.normalize, self.sort, self.weekdays_only, self.client.options.timezone);
+        Ok(stats)
+    }
+}
+
+/// Replace the levels with ones relative to the window's busiest day, so the same count
+/// gets the same color in a quiet year and a busy one alike
+fn normalize_levels(graph: &mut ContributionGraph) {
+    let max = graph.weeks.iter()
+        .flat_map(|week| week.days.iter())
+        .map(|day| day.count)
+        .max()
+        .unwrap_or(0);
+
+    for day in graph.weeks.iter_mut().flat_map(|week| week.days.iter_mut()) {
+        day.level = if max == 0 { 0 } else { (day.count * 4).div_ceil(max) as u8 };
+    }
+}
+
+fn read_users_file(path: &std::path::Path) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read users file {}", path.display()))?;
+    let users: Vec<String> = contents.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+
+    if users.is_empty() {
+        anyhow::bail!("No usernames found in {}", path.display());
+    }
+    Ok(users)
+}
+
+struct LeaderboardEntry {
+    username: String,
+    /// Total contributions and today's contributions, or why they couldn't be fetched
+    result: Result<(u32, u32)>,
+}
+
+/// Fetch every user's stats, a few at a time, keeping the input order
+async fn fetch_users_stats(client: &GitHubClient, users: &[String]) -> Vec<(String, Result<Stats>)> {
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(LEADERBOARD_CONCURRENCY));
+    let mut tasks = tokio::task::JoinSet::new();
+
+    for (index, username) in users.iter().enumerate() {
+        let client = client.for_user(username.clone());
+        let semaphore = Arc::clone(&semaphore);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (index, client.get_stats(&DEFAULT_QUANTILES).await)
+        });
+    }
+
+    let mut results: Vec<Option<Result<Stats>>> = users.iter().map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, result)) = joined {
+            results[index] = Some(result);
+        }
+    }
+    // A task that panicked left its slot empty; the user still gets a row, with the failure
+    results.into_iter()
+        .zip(users)
+        .map(|(result, username)| {
+            let result = result.unwrap_or_else(|| Err(anyhow::anyhow!("Fetching the stats of {} stopped unexpectedly", username)));
+            (username.clone(), result)
+        })
+        .collect()
+}
+
+async fn fetch_leaderboard(client: &GitHubClient, users: &[String]) -> Vec<LeaderboardEntry> {
+    fetch_users_stats(client, users).await
+        .into_iter()
+        .map(|(username, result)| {
+            let result = result.map(|stats| {
+                let summary = compute_summary(&stats.contribution_graph, DEFAULT_ACTIVE_THRESHOLD, false, DEFAULT_PERIOD_WEEK_START, client.options.timezone);
+                (stats.contribution_graph.total_contributions, summary.today)
+            });
+            LeaderboardEntry { username, result }
+        })
+        .collect()
+}
+
+/// Ranked table, highest total first, with users that failed listed at the bottom
+fn display_leaderboard(entries: &[LeaderboardEntry]) {
+    let mut ranked: Vec<(&str, u32, u32)> = entries.iter()
+        .filter_map(|entry| entry.result.as_ref().ok().map(|&(total, today)| (entry.username.as_str(), total, today)))
+        .collect();
+    ranked.sort_by_key(|&(_, total, _)| std::cmp::Reverse(total));
+
+    println!();
+    println!("{}", "Contributions Leaderboard:".bright_cyan().bold());
+    println!();
+    println!("{:<6} {:<30} {:<8} {:<8}",
+        "Rank".bright_white().bold(),
+        "User".bright_white().bold(),
+        "Total".bright_green().bold(),
+        "Today".bright_cyan().bold()
+    );
+    println!("{}", "─".repeat(55).bright_black());
+
+    for (i, (username, total, today)) in ranked.iter().enumerate() {
+        println!("{:<6} {:<30} {:<8} {:<8}",
+            format!("{}.", i + 1).bright_white(),
+            username.bright_blue().bold(),
+            total.to_string().bright_green(),
+            today.to_string().bright_cyan()
+        );
+    }
+
+    for entry in entries {
+        if let Err(e) = &entry.result {
+            println!("{:<6} {:<30} {}",
+                "✗".bright_red(),
+                entry.username.bright_black(),
+                e.to_string().bright_red()
+            );
+        }
+    }
+}
+
+/// Draw a placeholder graph and spin until `shutdown` fires (or its sender is dropped),
+/// then erase everything drawn so the real output starts on a clean spot
+async fn show_loading_animation(mut shutdown: tokio::sync::oneshot::Receiver<()>, options: DisplayOptions, last_day: NaiveDate) {
+    let mut frame_idx = 0;
+    let glyphs = glyphs(options.ascii);
+    let placeholder = glyphs.placeholder.bright_black();
+
+    // Keep every line within the terminal so none wrap and the line count below stays exact
+    let view = GraphView::fit(WEEKS_IN_YEAR, &options);
+    let weeks = view.width;
+    let week_start = options.grid_week_start();
+    let week_starts = trailing_week_starts(last_day, weeks, week_start);
+    let mut lines_drawn = 0;
+    
+    // Print the loading graph once - same dimensions as contribution graph
+    println!();
+    println!("       {}", month_labels(&week_starts, view.spacing.cell_width()));
+    lines_drawn += 2;
+    
+    // Print graph rows
+    for day_label in week_start.day_labels() {
+        print!("{:>6} ", day_label);
+        
+        for _ in 0..weeks {
+            print!("{}{}", placeholder, view.spacing.gap());
+        }
+        println!();
+        lines_drawn += 1;
+    }
+    
+    println!();
+    lines_drawn += 1;
+    
+    // Animate only the spinner, finishing the current frame before stopping
+    loop {
+        print!("\rLoading {} contributions...", glyphs.spinner[frame_idx].bright_blue());
+        stdout().flush().ok();
+        
+        frame_idx = (frame_idx + 1) % glyphs.spinner.len();
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(Duration::from_millis(150)) => {}
+        }
+    }
+
+    // Back to the first line we drew, then clear to the end of the screen
+    print!("\r\x1b[{}A\x1b[J", lines_drawn);
+    stdout().flush().ok();
+}
+
+/// Horizontal window over the weeks of the graph, panned with the arrow keys in interactive mode
+#[derive(Debug, Clone, Copy)]
+struct GraphView {
+    /// Index of the first visible week
+    start: usize,
+    /// Number of weeks that fit in the terminal
+    width: usize,
+    total_weeks: usize,
+    spacing: Spacing,
+}
+
+impl GraphView {
+    /// Fit as many weeks as the terminal allows, scrolled to the most recent ones.
+    /// Without an explicit `--spacing`, cells are packed tight only when the spaced graph wouldn't fit.
+    fn fit(total_weeks: usize, options: &DisplayOptions) -> Self {
+        let columns = terminal::size().map(|(cols, _)| cols as usize).unwrap_or(usize::MAX);
+        let available = columns.saturating_sub(DAY_LABEL_WIDTH);
+        let spacing = if options.show_counts {
+            // Adjacent numbers would run together
+            Spacing::Normal
+        } else {
+            options.spacing.unwrap_or(if available / CELL_WIDTH >= total_weeks { Spacing::Normal } else { Spacing::Tight })
+        };
+        // Tight cells still keep one space after the day labels
+        let available = match spacing {
+            Spacing::Tight => available.saturating_sub(1),
+            Spacing::Normal => available,
+        };
+        let width = (available / spacing.cell_width()).clamp(1, total_weeks.max(1));
+
+        Self {
+            start: total_weeks.saturating_sub(width),
+            width,
+            total_weeks,
+            spacing,
+        }
+    }
+
+    /// Every week at once, for output that isn't bound to the terminal's width
+    fn full(total_weeks: usize, options: &DisplayOptions) -> Self {
+        let spacing = if options.show_counts { Spacing::Normal } else { options.spacing.unwrap_or(Spacing::Normal) };
+        Self {
+            start: 0,
+            width: total_weeks.max(1),
+            total_weeks,
+            spacing,
+        }
+    }
+
+    fn is_partial(&self) -> bool {
+        self.width < self.total_weeks
+    }
+
+    fn scroll_left(&mut self) -> bool {
+        let start = self.start.saturating_sub(SCROLL_STEP_WEEKS);
+        let moved = start != self.start;
+        self.start = start;
+        moved
+    }
+
+    fn scroll_right(&mut self) -> bool {
+        let start = (self.start + SCROLL_STEP_WEEKS).min(self.total_weeks.saturating_sub(self.width));
+        let moved = start != self.start;
+        self.start = start;
+        moved
+    }
+}
+
+/// What the graph's daily counts are built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum GraphSource {
+    /// GitHub's contribution calendar: commits, issues, pull requests and reviews, one query
+    Calendar,
+    /// The user's commits to the repositories they committed to, dated locally; several requests per repository
+    Commits,
+}
+
+/// How the recent repositories are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum RepoSort {
+    /// Most recently pushed first, as GitHub lists them
+    Recent,
+    /// Most commits this month first
+    Commits,
+    /// Alphabetically by owner/name
+    Name,
+}
+
+/// Reorder `repos`, which come most recently pushed first; ties keep that order
+fn sort_repos(repos: &mut [RepositoryWithCommits], order: RepoSort) {
+    match order {
+        RepoSort::Recent => {}
+        RepoSort::Commits => repos.sort_by_key(|repo| std::cmp::Reverse(repo.month_commits)),
+        RepoSort::Name => repos.sort_by_cached_key(|repo| repo.full_name.to_lowercase()),
+    }
+}
+
+/// Space between graph cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Spacing {
+    /// Cells printed back to back, so a full year fits in about 110 columns
+    Tight,
+    /// One space before every cell
+    Normal,
+}
+
+impl Spacing {
+    fn cell_width(self) -> usize {
+        match self {
+            Spacing::Tight => TIGHT_CELL_WIDTH,
+            Spacing::Normal => CELL_WIDTH,
+        }
+    }
+
+    fn gap(self) -> &'static str {
+        match self {
+            Spacing::Tight => "",
+            Spacing::Normal => " ",
+        }
+    }
+
+}
+
+/// Rendering switches for the text graph
+#[derive(Debug, Clone, Copy, Default)]
+struct DisplayOptions {
+    show_counts: bool,
+    /// Draw with ASCII instead of emoji and box-drawing characters
+    ascii: bool,
+    /// Fixed cell spacing, or None to pick one from the terminal width
+    spacing: Option<Spacing>,
+    accent_today: bool,
+    legend_stats: bool,
+    /// Stop after the grid and the total line
+    no_footer: bool,
+    highlight_streak: bool,
+    /// Draw the gap before a week that starts a new month as a line
+    month_separators: bool,
+    /// Print the profile and repository URLs
+    show_url: bool,
+    /// Wrap URLs in OSC 8 escapes so the terminal makes them clickable
+    hyperlinks: bool,
+    /// Contributions needed for a day to count as active (0 is treated as 1)
+    active_threshold: u32,
+    /// Compute the footer from Monday to Friday only
+    weekdays_only: bool,
+    /// `--week-start`; without it the grid keeps GitHub's Sunday rows and the periods use Monday
+    week_start: Option<WeekStart>,
+    year_goal: Option<u32>,
+    /// Level colors of the text graph
+    palette: Palette,
+    /// Zone whose today the footer counts from
+    timezone: Tz,
+}
+
+impl DisplayOptions {
+    fn grid_week_start(&self) -> WeekStart {
+        self.week_start.unwrap_or(WeekStart::Sunday)
+    }
+
+    fn period_week_start(&self) -> WeekStart {
+        self.week_start.unwrap_or(DEFAULT_PERIOD_WEEK_START)
+    }
+}
+
+/// First day of a week
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum WeekStart {
+    Sunday,
+    Monday,
+}
+
+/// "This week" and "last week" start on Monday unless `--week-start` says otherwise
+const DEFAULT_PERIOD_WEEK_START: WeekStart = WeekStart::Monday;
+
+impl WeekStart {
+    fn weekday(self) -> chrono::Weekday {
+        match self {
+            WeekStart::Sunday => chrono::Weekday::Sun,
+            WeekStart::Monday => chrono::Weekday::Mon,
+        }
+    }
+
+    /// Position of `date` in its week, 0 for the first day
+    fn row(self, date: NaiveDate) -> usize {
+        date.weekday().days_since(self.weekday()) as usize
+    }
+
+    /// First day of the week `date` falls in
+    fn week_of(self, date: NaiveDate) -> NaiveDate {
+        date - chrono::Duration::days(self.row(date) as i64)
+    }
+
+    /// Labels of the graph's seven rows; GitHub only labels Monday, Wednesday and Friday
+    fn day_labels(self) -> [&'static str; 7] {
+        match self {
+            WeekStart::Sunday => ["", "Mon", "", "Wed", "", "Fri", ""],
+            WeekStart::Monday => ["Mon", "", "Wed", "", "Fri", "", ""],
+        }
+    }
+}
+
+/// The weeks as the text grid lays them out: the calendar's own Sunday weeks, or its days
+/// regrouped into weeks starting on `week_start`, which can add a column at either end
+fn grid_weeks(weeks: &[Week], week_start: WeekStart) -> std::borrow::Cow<'_, [Week]> {
+    if week_start == WeekStart::Sunday {
+        return std::borrow::Cow::Borrowed(weeks);
+    }
+    let mut regrouped: Vec<Week> = Vec::new();
+    let mut current = None;
+    for day in weeks.iter().flat_map(|week| &week.days) {
+        let week = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok().map(|date| week_start.week_of(date));
+        // A day without a parseable date stays in the week before it
+        if regrouped.is_empty() || (week.is_some() && week != current) {
+            regrouped.push(Week { days: Vec::new() });
+            current = week;
+        }
+        if let Some(last) = regrouped.last_mut() {
+            last.days.push(day.clone());
+        }
+    }
+    std::borrow::Cow::Owned(regrouped)
+}
+
+/// Characters the graph is drawn with, swappable for consoles that can't show Unicode
+#[derive(Debug)]
+struct Glyphs {
+    /// One cell per contribution level, each two columns wide
+    levels: [&'static str; 5],
+    placeholder: &'static str,
+    separator: &'static str,
+    /// One column wide, replacing the gap between cells
+    month_separator: &'static str,
+    private_marker: &'static str,
+    scroll_left: &'static str,
+    scroll_right: &'static str,
+    error_prefix: &'static str,
+    warning_marker: &'static str,
+    /// `--diff-graph` cells: first user ahead, second user ahead, tie or no activity
+    diff: [&'static str; 3],
+    /// Frames of the loading spinner
+    spinner: &'static [&'static str],
+}
+
+const UNICODE_GLYPHS: Glyphs = Glyphs {
+    levels: ["⬛", "🟩", "🟨", "🟧", "🟥"],
+    placeholder: "⬜",
+    separator: "─",
+    month_separator: "│",
+    private_marker: "⛨",
+    scroll_left: "◀",
+    scroll_right: "▶",
+    error_prefix: "❌ Error:",
+    warning_marker: "⚠",
+    diff: ["🟦", "🟥", "⬛"],
+    spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+};
+
+// Level glyphs are doubled so cells keep the two-column width of the emoji squares
+const ASCII_GLYPHS: Glyphs = Glyphs {
+    levels: ["..", "::", "--", "==", "##"],
+    placeholder: "..",
+    separator: "-",
+    month_separator: "|",
+    private_marker: "(private)",
+    scroll_left: "<",
+    scroll_right: ">",
+    error_prefix: "Error:",
+    warning_marker: "!",
+    diff: ["<<", ">>", ".."],
+    spinner: &["|", "/", "-", "\\"],
+};
+
+fn glyphs(ascii: bool) -> &'static Glyphs {
+    if ascii { &ASCII_GLYPHS } else { &UNICODE_GLYPHS }
+}
+
+/// How many colors the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ColorDepth {
+    /// 24-bit RGB, the theme's exact colors
+    Truecolor,
+    /// The xterm 256-color palette, theme colors mapped to the nearest entry
+    #[value(name = "256")]
+    Ansi256,
+    /// The basic 16 colors, with a fixed green, yellow, amber, red ramp
+    #[value(name = "16")]
+    Ansi16,
+    /// No colors at all
+    None,
+}
+
+impl ColorDepth {
+    /// Guess from `COLORTERM` and the terminfo name in `TERM`
+    fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorDepth::Truecolor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else if term == "dumb" {
+            ColorDepth::None
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// Whether stdout is a terminal known to understand OSC 8 hyperlinks; others would print the escapes
+fn terminal_supports_hyperlinks() -> bool {
+    if !stdout().is_terminal() {
+        return false;
+    }
+    let env = |name: &str| std::env::var(name).unwrap_or_default();
+    let vte_version: u32 = env("VTE_VERSION").parse().unwrap_or(0);
+    !env("WT_SESSION").is_empty()
+        || matches!(env("TERM_PROGRAM").as_str(), "iTerm.app" | "WezTerm" | "vscode" | "ghostty")
+        || env("TERM").contains("kitty")
+        || vte_version >= 5000
+}
+
+/// `text` as an OSC 8 link to `url`, or `text` alone when links aren't supported
+fn hyperlink(text: &str, url: &str, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Level colors for the text graph and the depth to draw them at
+#[derive(Debug, Clone, Copy)]
+struct Palette {
+    levels: [Rgb; 5],
+    depth: ColorDepth,
+    /// Theme cells that replace the glyphs' level squares
+    symbols: Option<[&'static str; 5]>,
+    legend: [&'static str; 2],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            levels: THEMES[0].levels,
+            depth: ColorDepth::Ansi16,
+            symbols: None,
+            legend: DEFAULT_LEGEND,
+        }
+    }
+}
+
+impl Palette {
+    /// Uncolored cell for a contribution level
+    fn symbol(&self, level: u8, glyphs: &'static Glyphs) -> &'static str {
+        self.symbols.unwrap_or(glyphs.levels)[(level as usize).min(4)]
+    }
+}
+
+/// Index of the xterm 256-color entry closest to `color`, from the 6x6x6 cube or the gray ramp
+fn nearest_ansi256(color: Rgb) -> u8 {
+    const CUBE_STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let distance = |a: Rgb, b: Rgb| {
+        let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2);
+        d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+    };
+    let nearest_step = |value: u8| {
+        (0..6).min_by_key(|&i| (CUBE_STEPS[i] as i32 - value as i32).abs()).unwrap_or(0)
+    };
+
+    let (r, g, b) = (nearest_step(color.0), nearest_step(color.1), nearest_step(color.2));
+    let cube = Rgb(CUBE_STEPS[r], CUBE_STEPS[g], CUBE_STEPS[b]);
+    let cube_index = 16 + 36 * r + 6 * g + b;
+
+    let average = (color.0 as u32 + color.1 as u32 + color.2 as u32) / 3;
+    let gray_step = (average.saturating_sub(8) / 10).min(23);
+    let gray_value = (8 + gray_step * 10) as u8;
+    let gray = Rgb(gray_value, gray_value, gray_value);
+
+    if distance(color, gray) < distance(color, cube) {
+        232 + gray_step as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+/// Undo a renderer's color override, back to what startup chose: off for `--no-color`,
+/// `NO_COLOR` or `--color-depth none`, otherwise whatever colored detects
+fn restore_color_choice(depth: ColorDepth) {
+    if depth == ColorDepth::None {
+        colored::control::set_override(false);
+    } else {
+        colored::control::unset_override();
+    }
+}
+
+/// Color `text` for a contribution level at the palette's depth
+fn paint_level(text: &str, level: u8, palette: &Palette) -> ColoredString {
+    let color = palette.levels[(level as usize).min(4)];
+    match palette.depth {
+        ColorDepth::Truecolor => text.truecolor(color.0, color.1, color.2),
+        // colored has no 256-color support, so the escape goes into the text itself
+        ColorDepth::Ansi256 if colored::control::SHOULD_COLORIZE.should_colorize() => {
+            ColoredString::from(format!("\x1b[38;5;{}m{}\x1b[0m", nearest_ansi256(color), text))
+        }
+        ColorDepth::Ansi256 | ColorDepth::None => ColoredString::from(text),
+        // There's no orange among the 16 colors; the dim yellow is drawn amber or brown by most palettes
+        ColorDepth::Ansi16 => match level {
+            1 => text.bright_green(),
+            2 => text.bright_yellow(),
+            3 => text.yellow(),
+            4 => text.bright_red(),
+            _ => text.bright_black(),
+        },
+    }
+}
+
+/// Colored square for a contribution level
+fn level_symbol(level: u8, glyphs: &'static Glyphs, palette: &Palette) -> ColoredString {
+    paint_level(palette.symbol(level, glyphs), level, palette)
+}
+
+/// Day count padded to the width of a square, colored like the square it replaces
+fn count_cell(day: &Day, palette: &Palette) -> ColoredString {
+    let text = match day.count {
+        0 => " ·".to_string(),
+        count if count > MAX_CELL_COUNT => " +".to_string(),
+        count => format!("{:>2}", count),
+    };
+    paint_level(&text, day.level, palette)
+}
+
+/// `--timezone`, or else the system's zone: TZ when it names one, then the zone the system is
+/// configured with. A system zone that can't be identified falls back to UTC with a warning.
+fn resolve_timezone(name: Option<&str>) -> Result<Tz> {
+    if let Some(name) = name {
+        return name.parse::<Tz>()
+            .map_err(|_| anyhow::anyhow!("Unknown time zone '{}' (expected an IANA name like Europe/Berlin, or UTC)", name));
+    }
+    let system = std::env::var("TZ").ok()
+        .and_then(|zone| zone.trim_start_matches(':').parse::<Tz>().ok())
+        .or_else(|| iana_time_zone::get_timezone().ok()?.parse::<Tz>().ok());
+    Ok(system.unwrap_or_else(|| {
+        eprintln!("{}", "Couldn't tell the system's time zone, so dates follow UTC; pass --timezone to pick one".bright_yellow());
+        Tz::UTC
+    }))
+}
+
+/// "3 days ago" style rendering of an RFC 3339 timestamp
+fn format_relative_time(timestamp: &str) -> String {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(timestamp) {
+        let now = chrono::Utc::now();
+        let duration = now.signed_duration_since(time);
+
+        if duration.num_days() > 0 {
+            format!("{} days ago", duration.num_days())
+        } else if duration.num_hours() > 0 {
+            format!("{} hours ago", duration.num_hours())
+        } else if duration.num_minutes() > 0 {
+            format!("{} minutes ago", duration.num_minutes())
+        } else {
+            "just now".to_string()
+        }
+    } else {
+        "unknown".to_string()
+    }
+}
+
+/// Cut `text` to at most `width` characters, ending in "…" when shortened
+fn truncate_text(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Month header over a row of week columns, each `cell_width` characters wide: a month's name
+/// starts above the first week beginning in it. When the oldest month has too few weeks for its
+/// name to fit, it is left unlabeled, and names that would run past the last column are dropped.
+/// Weeks with an unknown date count as part of the month before them.
+fn month_labels(week_starts: &[Option<NaiveDate>], cell_width: usize) -> String {
+    let width = week_starts.len() * cell_width;
+    let mut header = String::new();
+    let mut last_label_at = 0;
+    let mut previous_month = None;
+    for (idx, start) in week_starts.iter().enumerate() {
+        let Some(start) = start else {
+            continue;
+        };
+        if previous_month == Some(start.month()) {
+            continue;
+        }
+        previous_month = Some(start.month());
+
+        let column = idx * cell_width;
+        let name = start.format("%b").to_string();
+        if column + name.len() > width {
+            break;
+        }
+        if !header.is_empty() && header.len() >= column {
+            header.truncate(last_label_at);
+        }
+        header.push_str(&" ".repeat(column - header.len()));
+        header.push_str(&name);
+        last_label_at = column;
+    }
+    header
+}
+
+/// The first days of the `weeks` weeks that end with the one containing `today`,
+/// oldest first, matching how the text grid lays out its weeks
+fn trailing_week_starts(today: NaiveDate, weeks: usize, week_start: WeekStart) -> Vec<Option<NaiveDate>> {
+    let this_week = week_start.week_of(today);
+    (0..weeks)
+        .rev()
+        .map(|ago| Some(this_week - chrono::Duration::weeks(ago as i64)))
+        .collect()
+}
+
+/// Width of a repository name column: the longest of `names`, but at least `min`
+fn repo_column_width(names: &[String], min: usize) -> usize {
+    names.iter().map(|name| name.chars().count()).max().unwrap_or(0).max(min)
+}
+
+fn display_top_repos(username: &str, repos: &[TopRepository]) {
+    println!();
+    if repos.is_empty() {
+        println!("{}", format!("{} has no repositories yet", username).bright_black());
+        return;
+    }
+
+    println!("{}", "Top Repositories:".bright_cyan().bold());
+    println!();
+    let names: Vec<String> = repos.iter()
+        .map(|repo| if repo.is_private {
+            format!("{} ⛨", repo.name_with_owner)
+        } else {
+            repo.name_with_owner.clone()
+        })
+        .collect();
+    let name_width = repo_column_width(&names, 40);
+    println!("{:<4} {:<name_width$} {:<8} {:<14} {:<15}",
+        "No.".bright_white().bold(),
+        "Repository".bright_white().bold(),
+        "Stars".bright_yellow().bold(),
+        "Language".bright_cyan().bold(),
+        "Last Push".bright_white().bold()
+    );
+    println!("{}", "─".repeat(name_width + 45).bright_black());
+
+    for (i, (repo, repo_name)) in repos.iter().zip(names).enumerate() {
+        let language = repo.primary_language.as_ref().map_or("—", |language| language.name.as_str());
+        let pushed_display = repo.pushed_at.as_deref().map_or_else(|| "never".to_string(), format_relative_time);
+
+        println!("{:<4} {:<name_width$} {:<8} {:<14} {:<15}",
+            format!("{}.", i + 1).bright_white(),
+            repo_name.bright_blue().bold(),
+            format!("★ {}", repo.stargazer_count).bright_yellow(),
+            language.bright_cyan(),
+            pushed_display.bright_black()
+        );
+    }
+}
+
+/// Columns the text grid of `graph` takes, before fitting it to the terminal
+fn grid_week_count(graph: &ContributionGraph, options: &DisplayOptions) -> usize {
+    grid_weeks(&graph.weeks, options.grid_week_start()).len()
+}
+
+/// Month header and the seven day rows of the weeks in `view`, with every day drawn by `cell`
+/// `weeks` are the calendar's; they're regrouped for `--week-start` here, and `view` has to be
+/// sized for the regrouped count (see `grid_week_count`).
+fn write_grid(out: &mut dyn Write, weeks: &[Week], view: GraphView, options: &DisplayOptions, cell: &dyn Fn(&Day) -> ColoredString) -> Result<()> {
+    let glyphs = glyphs(options.ascii);
+    let week_start = options.grid_week_start();
+    let weeks = grid_weeks(weeks, week_start);
+    let visible_weeks: Vec<&Week> = weeks.iter()
+        .skip(view.start)
+        .take(view.width)
+        .collect();
+
+    // Display month labels, or the visible date range when only part of the graph fits
+    write!(out, "       ")?;
+    
+    if view.is_partial() {
+        let first = visible_weeks.first().and_then(|week| week.days.first());
+        let last = visible_weeks.last().and_then(|week| week.days.last());
+        if let (Some(first), Some(last)) = (first, last) {
+            let left = if view.start > 0 { format!("{} ", glyphs.scroll_left) } else { "  ".to_string() };
+            let right = if view.start + view.width < view.total_weeks { format!(" {}", glyphs.scroll_right) } else { String::new() };
+            write!(out, "{}{} – {}{}", left.bright_cyan(), first.date, last.date, right.bright_cyan())?;
+        }
+    } else {
+        let week_starts: Vec<Option<NaiveDate>> = visible_weeks.iter()
+            .map(|week| week.days.first().and_then(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()))
+            .collect();
+        write!(out, "{}", month_labels(&week_starts, view.spacing.cell_width()))?;
+    }
+    writeln!(out)?;
+    
+    // Weeks whose first day is in a different month than the week before's; the line takes the
+    // place of the gap, so tight spacing has nowhere to draw it
+    let month_of = |week: &Week| week.days.first()
+        .and_then(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok())
+        .map(|date| date.month());
+    let starts_month: Vec<bool> = (view.start..view.start + visible_weeks.len())
+        .map(|index| {
+            options.month_separators
+                && view.spacing == Spacing::Normal
+                && index > 0
+                && month_of(&weeks[index]) != month_of(&weeks[index - 1])
+        })
+        .collect();
+    let separator = glyphs.month_separator.bright_black();
+
+    // Display day labels and contribution graph
+    for (day_of_week, day_label) in week_start.day_labels().iter().enumerate() {
+        write!(out, "{:>6}", day_label)?;
+        if view.spacing == Spacing::Tight {
+            write!(out, " ")?;
+        }
+        
+        for (week, &starts_month) in visible_weeks.iter().zip(&starts_month) {
+            let gap = if starts_month { separator.clone() } else { view.spacing.gap().normal() };
+            // A week cut short by the start of the window begins on a later row
+            let offset = week.days.first()
+                .and_then(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok())
+                .map_or(0, |date| week_start.row(date));
+            let day = day_of_week.checked_sub(offset).and_then(|index| week.days.get(index));
+            if let Some(day) = day {
+                write!(out, "{}{}", gap, cell(day))?;
+            } else if options.show_counts {
+                write!(out, "{}  ", gap)?;
+            } else {
+                write!(out, "{}{}", gap, options.palette.symbol(0, glyphs))?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Which of the two users of `--diff-graph` contributed more on a day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DayWinner {
+    First,
+    Second,
+    Even,
+}
+
+/// Line two calendars up by date into one grid of Sunday-to-Saturday weeks, with each day's count
+/// being the two users' combined contributions. A date only one calendar has (the windows can differ
+/// by a day when the users' accounts resolve "today" differently) counts as zero for the other user.
+fn diff_graph(first: &ContributionGraph, second: &ContributionGraph) -> (Vec<Week>, HashMap<String, DayWinner>) {
+    let mut counts: std::collections::BTreeMap<NaiveDate, (u32, u32)> = std::collections::BTreeMap::new();
+    let days = |graph: &ContributionGraph| -> Vec<(NaiveDate, u32)> {
+        graph.weeks.iter()
+            .flat_map(|week| &week.days)
+            .filter_map(|day| Some((NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()?, day.count)))
+            .collect()
+    };
+    for (date, count) in days(first) {
+        counts.entry(date).or_default().0 += count;
+    }
+    for (date, count) in days(second) {
+        counts.entry(date).or_default().1 += count;
+    }
+
+    let mut weeks: Vec<Week> = Vec::new();
+    let mut winners = HashMap::new();
+    let mut current_week_start = None;
+    for (date, (first_count, second_count)) in counts {
+        let week_start = date - chrono::Duration::days(date.weekday().num_days_from_sunday() as i64);
+        if current_week_start != Some(week_start) {
+            weeks.push(Week { days: Vec::new() });
+            current_week_start = Some(week_start);
+        }
+        let winner = match first_count.cmp(&second_count) {
+            std::cmp::Ordering::Greater => DayWinner::First,
+            std::cmp::Ordering::Less => DayWinner::Second,
+            std::cmp::Ordering::Equal => DayWinner::Even,
+        };
+        let date = date.format("%Y-%m-%d").to_string();
+        winners.insert(date.clone(), winner);
+        if let Some(week) = weeks.last_mut() {
+            week.days.push(Day { date, count: first_count + second_count, level: 0 });
+        }
+    }
+    (weeks, winners)
+}
+
+/// Head-to-head grid of two users, followed by how many days each of them came out ahead
+fn display_diff_graph(first: &Stats, second: &Stats, options: &DisplayOptions, out: &mut dyn Write) -> Result<()> {
+    let glyphs = glyphs(options.ascii);
+    let (weeks, winners) = diff_graph(&first.contribution_graph, &second.contribution_graph);
+    let paint = |winner: DayWinner| match winner {
+        DayWinner::First => glyphs.diff[0].bright_blue(),
+        DayWinner::Second => glyphs.diff[1].bright_red(),
+        DayWinner::Even => glyphs.diff[2].bright_black(),
+    };
+    let cell = |day: &Day| paint(winners.get(&day.date).copied().unwrap_or(DayWinner::Even));
+
+    writeln!(out)?;
+    writeln!(out, "{} vs {}", first.username.bright_blue().bold(), second.username.bright_red().bold())?;
+    let view = GraphView::fit(grid_weeks(&weeks, options.grid_week_start()).len(), options);
+    write_grid(out, &weeks, view, options, &cell)?;
+    writeln!(out)?;
+
+    let tally = |wanted: DayWinner| winners.values().filter(|&&winner| winner == wanted).count();
+    writeln!(out, "{} {} ahead on {} days | {} {} ahead on {} days | {} even on {} days",
+        paint(DayWinner::First),
+        first.username,
+        tally(DayWinner::First).to_string().bright_blue(),
+        paint(DayWinner::Second),
+        second.username,
+        tally(DayWinner::Second).to_string().bright_red(),
+        paint(DayWinner::Even),
+        tally(DayWinner::Even)
+    )?;
+    writeln!(out, "Total: {} {} | {} {}",
+        first.username,
+        first.contribution_graph.total_contributions.to_string().bright_blue(),
+        second.username,
+        second.contribution_graph.total_contributions.to_string().bright_red()
+    )?;
+    Ok(())
+}
+
+fn display_contribution_graph(stats: &Stats, view: GraphView, options: &DisplayOptions, out: &mut dyn Write) -> Result<()> {
+    let glyphs = glyphs(options.ascii);
+    writeln!(out)?;
+    if stats.limited_history {
+        writeln!(out, "{}", format!(
+            "{} Unauthenticated, limited history: approximated from public events of the last {} days. Pass --token or run 'gh auth login' for the full calendar.",
+            glyphs.warning_marker,
+            EVENTS_HISTORY_DAYS
+        ).bright_yellow())?;
+    }
+    if options.show_url {
+        let url = format!("https://github.com/{}", stats.username);
+        writeln!(out, "{}", hyperlink(&url, &url, options.hyperlinks).bright_blue())?;
+    }
+    
+    // A blank grid and a row of zeros would only look like something failed
+    if stats.contribution_graph.total_contributions == 0 {
+        let period = match stats.contribution_graph.date_range() {
+            Some((start, end)) => format!("between {} and {}", start, end),
+            None => "in this period".to_string(),
+        };
+        writeln!(out, "{}", format!("No contributions found for {} {}", stats.username, period).bright_yellow())?;
+        return write_repo_tables(stats, options, glyphs, out);
+    }
+
+    // No cell carries this date when the window ends before today, so nothing gets accented
+    let today = today_in(options.timezone).format("%Y-%m-%d").to_string();
+    let streak: HashSet<&str> = if options.highlight_streak {
+        current_streak_days(&stats.contribution_graph, options.active_threshold, options.weekdays_only).iter().map(|day| day.date.as_str()).collect()
+    } else {
+        HashSet::new()
+    };
+    let cell = |day: &Day| {
+        let cell = if options.show_counts {
+            count_cell(day, &options.palette)
+        } else {
+            level_symbol(day.level, glyphs, &options.palette)
+        };
+        let cell = if streak.contains(day.date.as_str()) { cell.on_bright_black() } else { cell };
+        if options.accent_today && day.date == today { cell.reversed() } else { cell }
+    };
+    write_grid(out, &stats.contribution_graph.weeks, view, options, &cell)?;
+    
+    writeln!(out)?;
+
+    if options.no_footer {
+        writeln!(out, "Total: {} contributions", stats.contribution_graph.total_contributions.to_string().bright_green())?;
+        return Ok(());
+    }
+    
+    // Calculate additional stats
+    let summary = compute_summary(&stats.contribution_graph, options.active_threshold, options.weekdays_only, options.period_week_start(), options.timezone);
+    
+    // Week comparison
+    let week_diff = summary.week_delta;
+    let comparison = if week_diff > 0 {
+        format!(" ({} more than last week)", week_diff.to_string().bright_green())
+    } else if week_diff < 0 {
+        format!(" ({} less than last week)", (-week_diff).to_string().bright_red())
+    } else {
+        " (same as last week)".to_string()
+    };
+    
+    // Single line with all stats and comparison
+    writeln!(out, "Today: {} | This week: {}{} | This month: {} | This year: {}", 
+        summary.today.to_string().bright_green(),
+        summary.this_week.to_string().bright_green(),
+        comparison,
+        summary.this_month.to_string().bright_green(),
+        summary.this_year.to_string().bright_green()
+    )?;
+    let threshold = if options.active_threshold > 1 {
+        format!(" (≥ {} contributions)", options.active_threshold)
+    } else {
+        String::new()
+    };
+    writeln!(out, "Active {}/{} days{} — {} consistency | This month: {}/{} | Current streak: {} | Longest streak: {}",
+        summary.active_days.to_string().bright_green(),
+        summary.total_days,
+        threshold,
+        format!("{:.0}%", summary.consistency * 100.0).bright_green(),
+        summary.month_active_days.to_string().bright_green(),
+        summary.month_days,
+        summary.current_streak.to_string().bright_green(),
+        summary.longest_streak.to_string().bright_green()
+    )?;
+    if let Some(breakdown) = stats.breakdown {
+        writeln!(out, "{} contributions: {} commits | {} pull requests | {} issues | {} reviews",
+            stats.contribution_graph.total_contributions.to_string().bright_green(),
+            breakdown.commits.to_string().bright_green(),
+            breakdown.pull_requests.to_string().bright_green(),
+            breakdown.issues.to_string().bright_green(),
+            breakdown.reviews.to_string().bright_green()
+        )?;
+    }
+    writeln!(out, "{}", format!("Private contributions: {}", private_contributions_note(stats)).bright_black())?;
+    if let Some(busiest) = most_active_weekday(&stats.contribution_graph, options.weekdays_only, options.timezone) {
+        writeln!(out, "Most active: {} (avg {:.1}/day)", busiest.weekday.bright_green(), busiest.average)?;
+    }
+    if options.weekdays_only {
+        writeln!(out, "{}", "Weekdays only: Saturdays and Sundays are left out of these figures (--weekdays-only)".bright_black())?;
+    }
+    if let Some(goal) = options.year_goal {
+        let today = today_in(options.timezone);
+        match project_year_total(summary.this_year, today) {
+            Some(projected) => {
+                let difference = projected as i64 - goal as i64;
+                let pace = if difference >= 0 {
+                    format!("ahead by {}", difference).bright_green()
+                } else {
+                    format!("behind by {}", -difference).bright_red()
+                };
+                writeln!(out, "Goal: {} | On pace for: {} ({})",
+                    goal.to_string().bright_cyan(),
+                    projected.to_string().bright_green(),
+                    pace
+                )?;
+            }
+            None => writeln!(out, "Goal: {} | {}", goal.to_string().bright_cyan(), "too early in the year to project".bright_black())?,
+        }
+    }
+    if let Some((start, end)) = stats.contribution_graph.date_range() {
+        writeln!(out, "Showing {} to {} {}", start, end, format!("(dates and times in {})", options.timezone.name()).bright_black())?;
+    } else {
+        writeln!(out, "{}", format!("(dates and times in {})", options.timezone.name()).bright_black())?;
+    }
+    
+    // Legend
+    writeln!(out)?;
+    write!(out, "{} ", options.palette.legend[0])?;
+    for level in 0..=4 {
+        write!(out, "{} ", level_symbol(level, glyphs, &options.palette))?;
+    }
+    writeln!(out, "{}", options.palette.legend[1])?;
+    if options.show_counts {
+        writeln!(out, "{}", format!("Cells show daily counts, + means more than {}", MAX_CELL_COUNT).bright_black())?;
+    }
+    if options.legend_stats {
+        let total = stats.contribution_graph.weeks.iter().flat_map(|week| &week.days).map(|day| day.count).sum::<u32>();
+        for (level, bucket) in level_distribution(&stats.contribution_graph).iter().enumerate() {
+            let share = if total > 0 { bucket.contributions as f64 * 100.0 / total as f64 } else { 0.0 };
+            writeln!(out, "     {} {:>4} days {:>5.1}% of contributions",
+                level_symbol(level as u8, glyphs, &options.palette),
+                bucket.days,
+                share
+            )?;
+        }
+    }
+
+    write_repo_tables(stats, options, glyphs, out)
+}
+
+/// Whether the calendar's numbers include private contributions, for the summary
+fn private_contributions_note(stats: &Stats) -> String {
+    let restricted = stats.breakdown.map_or(0, |breakdown| breakdown.restricted);
+    if stats.private_excluded {
+        "removed (--public-only)".to_string()
+    } else if stats.viewer_is_user {
+        "included".to_string()
+    } else if restricted > 0 {
+        // The user shows private contribution counts on their profile, but not what they were
+        format!("{} included, without details", restricted)
+    } else {
+        "not visible to this token; only querying yourself with your own token shows them".to_string()
+    }
+}
+
+/// `--org`: the organization's recent repositories, as the table under a user's graph
+fn display_org_repos(org: &str, repos: Vec<RepositoryWithCommits>, options: &DisplayOptions) -> Result<()> {
+    let mut out = stdout();
+    writeln!(out)?;
+    if repos.is_empty() {
+        writeln!(out, "{}", format!("{} has no repositories visible to this token", org).bright_black())?;
+        return Ok(());
+    }
+    writeln!(out, "{}", format!("Organization {}", org).bright_cyan().bold())?;
+    let stats = Stats {
+        username: org.to_string(),
+        // The listing holds whatever the token can see, private repositories included
+        viewer_is_user: true,
+        private_excluded: false,
+        limited_history: false,
+        contribution_graph: ContributionGraph { weeks: Vec::new(), total_contributions: 0 },
+        recent_repos: repos,
+        pinned_repos: Vec::new(),
+        breakdown: None,
+        most_active_weekday: None,
+        punchcard: None,
+    };
+    write_repo_tables(&stats, options, glyphs(options.ascii), &mut out)
+}
+
+/// The recent and pinned repository tables under the text graph
+fn write_repo_tables(stats: &Stats, options: &DisplayOptions, glyphs: &Glyphs, out: &mut dyn Write) -> Result<()> {
+    // Display latest updated repositories with commit counts
+    if !stats.recent_repos.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "{}", "Latest Updated Repositories:".bright_cyan().bold())?;
+        writeln!(out)?;
+
+        // Without clickable names, the URLs get a column of their own
+        let url_column = options.show_url && !options.hyperlinks;
+
+        let names: Vec<String> = stats.recent_repos.iter()
+            .map(|repo| if repo.is_private {
+                format!("{} {}", repo.full_name, glyphs.private_marker)
+            } else {
+                repo.full_name.clone()
+            })
+            .collect();
+        let name_width = repo_column_width(&names, REPO_COLUMN_WIDTH);
+
+        // Column headers with color coding
+        write!(out, "{:<4} {:<name_width$} {:<8} {:<10} {:<12} {:<15}",
+            "No.".bright_white().bold(),
+            "Repository".bright_white().bold(),
+            "Today".bright_green().bold(),
+            "This Week".bright_cyan().bold(),
+            "This Month".bright_yellow().bold(),
+            "Last Updated".bright_white().bold()
+        )?;
+        if url_column {
+            write!(out, " {}", "URL".bright_white().bold())?;
+        }
+        writeln!(out)?;
+
+        // Separator line, as wide as the columns
+        writeln!(out, "{}", glyphs.separator.repeat(name_width + 50).bright_black())?;
+
+        for (i, (repo, repo_name)) in stats.recent_repos.iter().zip(names).enumerate() {
+            let pushed_display = format_relative_time(&repo.pushed_at);
+
+            let url = format!("https://github.com/{}", repo.full_name);
+            // The escapes don't take up columns, so pad the visible name before wrapping it
+            let repo_name = if options.show_url && options.hyperlinks {
+                let padding = " ".repeat(name_width.saturating_sub(repo_name.chars().count()));
+                format!("{}{}", hyperlink(&repo_name, &url, true), padding)
+            } else {
+                repo_name
+            };
+            let url_cell = if url_column { format!(" {}", url.bright_black()) } else { String::new() };
+
+            // Repositories past --scan-repos weren't counted, which is different from zero commits
+            let count = |commits: u32| if repo.scanned { commits.to_string() } else { "—".to_string() };
+
+            if repo.error.is_some() {
+                writeln!(out, "{:<4} {:<name_width$} {:<32} {:<15}{}",
+                    format!("{}.", i + 1).bright_white(),
+                    repo_name.bright_blue().bold(),
+                    "failed to fetch".bright_red(),
+                    pushed_display.bright_black(),
+                    url_cell
+                )?;
+                continue;
+            }
+
+            writeln!(out, "{:<4} {:<name_width$} {:<8} {:<10} {:<12} {:<15}{}",
+                format!("{}.", i + 1).bright_white(),
+                repo_name.bright_blue().bold(),
+                count(repo.today_commits).bright_green(),
+                count(repo.week_commits).bright_cyan(),
+                count(repo.month_commits).bright_yellow(),
+                pushed_display.bright_black(),
+                url_cell
+            )?;
+
+            for commit in &repo.commits {
+                writeln!(out, "     {} {} {}",
+                    commit.sha.bright_yellow(),
+                    commit.date.bright_black(),
+                    truncate_text(&commit.message, COMMIT_MESSAGE_WIDTH)
+                )?;
+            }
+            let unlisted = (repo.month_commits as usize).saturating_sub(repo.commits.len());
+            if !repo.commits.is_empty() && unlisted > 0 {
+                writeln!(out, "     {}", format!("+{} more", unlisted).bright_black())?;
+            }
+        }
+
+        // The calendar and the commit columns come from different sources, so say what each covers
+        writeln!(out)?;
+        let failed = stats.recent_repos.iter().filter(|repo| repo.error.is_some()).count();
+        if failed > 0 {
+            writeln!(out, "{}", format!("Note: commit counts for {} repositories could not be fetched (see \"error\" in --format json).", failed).bright_yellow())?;
+        }
+        let unparseable: u32 = stats.recent_repos.iter().map(|repo| repo.unparseable_dates).sum();
+        if unparseable > 0 {
+            writeln!(out, "{}", format!("Note: {} commits had unparseable dates and are only counted under This Month (--verbose lists them).", unparseable).bright_yellow())?;
+        }
+        if stats.viewer_is_user {
+            writeln!(out, "{}", "Note: commit counts only cover the default branch of the repositories above.".bright_black())?;
+        } else {
+            writeln!(out, "{}", "Note: commit counts only cover public commits on the default branch of the repositories above.".bright_black())?;
+        }
+    }
+
+    if !stats.pinned_repos.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "{}", "Pinned Repositories:".bright_cyan().bold())?;
+        writeln!(out)?;
+        let names: Vec<String> = stats.pinned_repos.iter().map(|repo| repo.name_with_owner.clone()).collect();
+        let name_width = repo_column_width(&names, 40);
+        writeln!(out, "{:<4} {:<name_width$} {:<8} {:<14} {}",
+            "No.".bright_white().bold(),
+            "Repository".bright_white().bold(),
+            "Stars".bright_yellow().bold(),
+            "Language".bright_cyan().bold(),
+            "Description".bright_white().bold()
+        )?;
+        writeln!(out, "{}", glyphs.separator.repeat(name_width + 45).bright_black())?;
+
+        for (i, repo) in stats.pinned_repos.iter().enumerate() {
+            let language = repo.primary_language.as_ref().map_or("—", |language| language.name.as_str());
+            let description = repo.description.as_deref().map_or_else(String::new, |description| truncate_text(description, PINNED_DESCRIPTION_WIDTH));
+            writeln!(out, "{:<4} {:<name_width$} {:<8} {:<14} {}",
+                format!("{}.", i + 1).bright_white(),
+                repo.name_with_owner.bright_blue().bold(),
+                format!("★ {}", repo.stargazer_count).bright_yellow(),
+                language.bright_cyan(),
+                description.bright_black()
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Convert back to GitHub's own `contributionCalendar` shape, for tools that already consume it
+fn to_github_calendar(graph: &ContributionGraph) -> ContributionCalendar {
+    ContributionCalendar {
+        total_contributions: graph.total_contributions,
+        weeks: graph.weeks.iter()
+            .map(|week| GraphQLWeek {
+                contribution_days: week.days.iter()
+                    .map(|day| GraphQLDay {
+                        date: day.date.clone(),
+                        contribution_count: day.count,
+                        contribution_level: CONTRIBUTION_LEVELS[(day.level as usize).min(4)].to_string(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+/// Turns fetched stats into one output format
+trait Renderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()>;
+}
+
+/// Settings from the command line that renderers may need beyond the stats themselves
+struct RenderContext {
+    theme: &'static Theme,
+    svg_responsive: bool,
+    display: DisplayOptions,
+    /// Rendering into an `--output` file rather than the terminal
+    to_file: bool,
+}
+
+/// Colored terminal graph with summary, legend and repository table
+struct TextRenderer {
+    options: DisplayOptions,
+    /// Every week instead of what fits the terminal, for files
+    full_width: bool,
+}
+
+impl Renderer for TextRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        let weeks = grid_week_count(&stats.contribution_graph, &self.options);
+        let view = if self.full_width { GraphView::full(weeks, &self.options) } else { GraphView::fit(weeks, &self.options) };
+        display_contribution_graph(stats, view, &self.options, out)
+    }
+}
+
+/// The full-width text graph with its colors kept, for saving a snapshot that `cat` redisplays
+struct AnsiTextRenderer {
+    options: DisplayOptions,
+}
+
+impl Renderer for AnsiTextRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        let view = GraphView::full(grid_week_count(&stats.contribution_graph, &self.options), &self.options);
+        // Colors are the point of this format, even when writing to a file or pipe
+        colored::control::set_override(true);
+        let result = display_contribution_graph(stats, view, &self.options, out);
+        restore_color_choice(self.options.palette.depth);
+        result
+    }
+}
+
+/// Our own `Stats` shape, which `--diff` can read back
+struct JsonRenderer;
+
+/// `Stats` plus the dates it covers; the extra fields are ignored when `--diff` reads it back
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    #[serde(flatten)]
+    stats: &'a Stats,
+    window_start: Option<&'a str>,
+    window_end: Option<&'a str>,
+}
+
+impl Renderer for JsonRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        let range = stats.contribution_graph.date_range();
+        let output = JsonOutput {
+            stats,
+            window_start: range.map(|(start, _)| start),
+            window_end: range.map(|(_, end)| end),
+        };
+        writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+        Ok(())
+    }
+}
+
+struct GithubJsonRenderer;
+
+impl Renderer for GithubJsonRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        writeln!(out, "{}", serde_json::to_string_pretty(&to_github_calendar(&stats.contribution_graph))?)?;
+        Ok(())
+    }
+}
+
+/// Badge colors by the smallest total that earns them, highest first
+const BADGE_COLORS: [(u32, &str); 5] = [
+    (1000, "brightgreen"),
+    (500, "green"),
+    (100, "yellowgreen"),
+    (1, "yellow"),
+    (0, "lightgrey"),
+];
+
+fn badge_color(total: u32) -> &'static str {
+    BADGE_COLORS.iter()
+        .find(|(threshold, _)| total >= *threshold)
+        .map_or("lightgrey", |(_, color)| color)
+}
+
+/// The total as a shields.io endpoint badge (https://shields.io/badges/endpoint-badge)
+struct BadgeRenderer;
+
+impl Renderer for BadgeRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        let total = stats.contribution_graph.total_contributions;
+        let badge = serde_json::json!({
+            "schemaVersion": 1,
+            "label": "contributions",
+            "message": total.to_string(),
+            "color": badge_color(total),
         });
-        
-        let response = self.client
-            .post("https://api.github.com/graphql")
-            .json(&request_body)
-            .send()
-            .await?;
-            
-        if !response.status().is_success() {
-            anyhow::bail!("GraphQL request failed: {}", response.status());
+        writeln!(out, "{}", serde_json::to_string(&badge)?)?;
+        Ok(())
+    }
+}
+
+/// Contributions per ISO week, for reporting tools that work at weekly granularity
+struct WeeklyCsvRenderer {
+    active_threshold: u32,
+}
+
+impl Renderer for WeeklyCsvRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        writeln!(out, "iso_year,iso_week,total,active_days")?;
+        for week in weekly_totals(&stats.contribution_graph, self.active_threshold) {
+            writeln!(out, "{},{},{},{}", week.iso_year, week.iso_week, week.total, week.active_days)?;
         }
-        
-        let graphql_response: GraphQLResponse = response.json().await?;
-        let user_data = graphql_response.data.user;
-        let calendar = user_data.contributions_collection.contribution_calendar;
-        
-        // Convert GraphQL data to our format
-        let mut weeks = Vec::new();
-        
-        for graphql_week in calendar.weeks {
-            let mut week = Week { days: Vec::new() };
-            
-            for graphql_day in graphql_week.contribution_days {
-                let level = match graphql_day.contribution_level.as_str() {
-                    "NONE" => 0,
-                    "FIRST_QUARTILE" => 1,
-                    "SECOND_QUARTILE" => 2,
-                    "THIRD_QUARTILE" => 3,
-                    "FOURTH_QUARTILE" => 4,
-                    _ => 0,
+        Ok(())
+    }
+}
+
+/// Every day as `date,count,level`, then the recent repositories, for spreadsheets
+struct CsvRenderer;
+
+impl Renderer for CsvRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        write_csv(stats, out)
+    }
+}
+
+/// Two CSV sections separated by a blank line: one row per day, oldest first and including
+/// days without contributions so the series has no gaps, then one row per recent repository
+fn write_csv(stats: &Stats, out: &mut dyn Write) -> Result<()> {
+    writeln!(out, "date,count,level")?;
+    for day in stats.contribution_graph.weeks.iter().flat_map(|week| &week.days) {
+        writeln!(out, "{},{},{}", day.date, day.count, day.level)?;
+    }
+
+    if !stats.recent_repos.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "repository,pushed_at,private,status,today,week,month")?;
+        for repo in &stats.recent_repos {
+            // Counts are left empty unless they were fetched, so a real 0 stands out
+            let status = match (&repo.error, repo.scanned) {
+                (Some(_), _) => "failed",
+                (None, false) => "not_scanned",
+                (None, true) => "counted",
+            };
+            let counts = if status == "counted" {
+                format!("{},{},{}", repo.today_commits, repo.week_commits, repo.month_commits)
+            } else {
+                ",,".to_string()
+            };
+            writeln!(out, "{},{},{},{},{}", repo.full_name, repo.pushed_at, repo.is_private, status, counts)?;
+        }
+    }
+    Ok(())
+}
+
+/// GitHub-flavored Markdown for a profile README: a totals line and the recent repositories as a table
+struct MarkdownRenderer {
+    options: DisplayOptions,
+}
+
+impl Renderer for MarkdownRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        let summary = compute_summary(&stats.contribution_graph, self.options.active_threshold, self.options.weekdays_only, self.options.period_week_start(), self.options.timezone);
+        let period = stats.contribution_graph.date_range()
+            .map(|(start, end)| format!(" from {} to {}", start, end))
+            .unwrap_or_default();
+        writeln!(out, "**{}** contributions{} · today {} · this week {} · this month {} · current streak {} days",
+            stats.contribution_graph.total_contributions,
+            period,
+            summary.today,
+            summary.this_week,
+            summary.this_month,
+            summary.current_streak
+        )?;
+
+        if !stats.recent_repos.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "| Repository | Today | Week | Month | Last Updated |")?;
+            writeln!(out, "| --- | :---: | :---: | :---: | --- |")?;
+            for repo in &stats.recent_repos {
+                // Same states as the text table: a failed count isn't zero, an unscanned one is unknown
+                let [today, week, month] = if repo.error.is_some() {
+                    ["failed to fetch".to_string(), String::new(), String::new()]
+                } else {
+                    let count = |commits: u32| if repo.scanned { commits.to_string() } else { "—".to_string() };
+                    [count(repo.today_commits), count(repo.week_commits), count(repo.month_commits)]
                 };
-                
-                week.days.push(Day {
-                    date: graphql_day.date,
-                    count: graphql_day.contribution_count,
-                    level,
-                });
+                writeln!(out, "| [{}](https://github.com/{}) | {} | {} | {} | {} |",
+                    repo.full_name,
+                    repo.full_name,
+                    today,
+                    week,
+                    month,
+                    format_relative_time(&repo.pushed_at)
+                )?;
             }
-            
-            weeks.push(week);
         }
-        
-        let contribution_graph = ContributionGraph {
-            weeks,
-            total_contributions: calendar.total_contributions,
+        Ok(())
+    }
+}
+
+/// Just the total contribution count, for shell scripts
+struct TotalRenderer;
+
+impl Renderer for TotalRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        writeln!(out, "{}", stats.contribution_graph.total_contributions)?;
+        Ok(())
+    }
+}
+
+/// The footer's period statistics as a bordered table, without the grid or the repositories
+struct TableRenderer {
+    options: DisplayOptions,
+}
+
+impl Renderer for TableRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        let summary = compute_summary(&stats.contribution_graph, self.options.active_threshold, self.options.weekdays_only, self.options.period_week_start(), self.options.timezone);
+        let delta = match summary.week_delta.cmp(&0) {
+            std::cmp::Ordering::Greater => format!("+{}", summary.week_delta).bright_green(),
+            std::cmp::Ordering::Less => summary.week_delta.to_string().bright_red(),
+            std::cmp::Ordering::Equal => "0".normal(),
         };
+        let rows = [
+            ("Today", summary.today.to_string().bright_green()),
+            ("This week", summary.this_week.to_string().bright_green()),
+            ("Last week", summary.last_week.to_string().normal()),
+            ("Week over week", delta),
+            ("This month", summary.this_month.to_string().bright_green()),
+            ("This year", summary.this_year.to_string().bright_green()),
+            ("Active days", format!("{}/{}", summary.active_days, summary.total_days).normal()),
+            ("Consistency", format!("{:.0}%", summary.consistency * 100.0).normal()),
+            ("Current streak", format!("{} days", summary.current_streak).bright_green()),
+            ("Longest streak", format!("{} days", summary.longest_streak).normal()),
+        ];
+        let rows: Vec<(String, String)> = rows.into_iter().map(|(label, value)| (label.to_string(), value.to_string())).collect();
+        write_box_table(out, ("Statistic", "Value"), &rows, self.options.ascii)
+    }
+}
 
-        // Get commit counts for each repository (single API call per repo)
-        let mut repos_with_commits = Vec::new();
-        for repo in user_data.repositories.nodes {
-            let full_name = format!("{}/{}", repo.owner.login, repo.name);
-            let (today_commits, week_commits, month_commits) = self.get_all_commit_counts(&full_name).await.unwrap_or((0, 0, 0));
-            repos_with_commits.push(RepositoryWithCommits {
-                name: repo.name,
-                full_name: full_name.clone(),
-                pushed_at: repo.pushed_at,
-                is_private: repo.is_private,
-                today_commits,
-                week_commits,
-                month_commits,
-            });
+/// Characters visible in a terminal, leaving out ANSI escape sequences
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end with a letter
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
         }
+    }
+    width
+}
+
+/// Two-column table with a header row, labels left-aligned and values right-aligned.
+/// Widths are measured without color escapes so colored values still line up.
+fn write_box_table(out: &mut dyn Write, header: (&str, &str), rows: &[(String, String)], ascii: bool) -> Result<()> {
+    // Corners and junctions: top, middle and bottom rows, each left/inner/right
+    let (horizontal, vertical, top, middle, bottom) = if ascii {
+        ("-", "|", ["+", "+", "+"], ["+", "+", "+"], ["+", "+", "+"])
+    } else {
+        ("─", "│", ["┌", "┬", "┐"], ["├", "┼", "┤"], ["└", "┴", "┘"])
+    };
+    let label_width = rows.iter().map(|(label, _)| visible_width(label)).chain([header.0.len()]).max().unwrap_or(0);
+    let value_width = rows.iter().map(|(_, value)| visible_width(value)).chain([header.1.len()]).max().unwrap_or(0);
+    let rule = |[left, inner, right]: [&str; 3]| {
+        format!("{}{}{}{}{}", left, horizontal.repeat(label_width + 2), inner, horizontal.repeat(value_width + 2), right)
+    };
+    let pad = |text: &str, width: usize| " ".repeat(width.saturating_sub(visible_width(text)));
+
+    writeln!(out, "{}", rule(top))?;
+    writeln!(out, "{v} {}{} {v} {}{} {v}",
+        header.0.bold(), pad(header.0, label_width),
+        pad(header.1, value_width), header.1.bold(),
+        v = vertical
+    )?;
+    writeln!(out, "{}", rule(middle))?;
+    for (label, value) in rows {
+        writeln!(out, "{v} {}{} {v} {}{} {v}",
+            label, pad(label, label_width),
+            pad(value, value_width), value,
+            v = vertical
+        )?;
+    }
+    writeln!(out, "{}", rule(bottom))?;
+    Ok(())
+}
+
+struct SvgRenderer {
+    theme: &'static Theme,
+    responsive: bool,
+    week_start: WeekStart,
+}
 
-        Ok((contribution_graph, repos_with_commits))
+impl Renderer for SvgRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        write!(out, "{}", render_svg(stats, self.theme, self.responsive, self.week_start)?)?;
+        Ok(())
     }
+}
 
-    /// Get commit counts for today, this week, and this month for a repository
-    /// Uses the same time period calculations as the main stats to ensure consistency
-    async fn get_all_commit_counts(&self, full_repo_name: &str) -> Result<(u32, u32, u32)> {
-        // Use local time zone (same as main stats) to ensure consistency
-        let today = chrono::Local::now().date_naive();
-        let today_start = chrono::Local.from_local_datetime(&today.and_hms_opt(0, 0, 0).unwrap()).unwrap().with_timezone(&chrono::Utc);
-        let today_end = chrono::Local.from_local_datetime(&today.and_hms_opt(23, 59, 59).unwrap()).unwrap().with_timezone(&chrono::Utc);
+struct HtmlRenderer {
+    theme: &'static Theme,
+    week_start: WeekStart,
+}
 
-        // This week: Monday of current week to now (same calculation as main stats)
-        let week_start_date = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
-        let week_start = chrono::Local.from_local_datetime(&week_start_date.and_hms_opt(0, 0, 0).unwrap()).unwrap().with_timezone(&chrono::Utc);
+impl Renderer for HtmlRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        write!(out, "{}", render_html(stats, self.theme, self.week_start))?;
+        Ok(())
+    }
+}
 
-        // This month: 1st day of current month to now (same calculation as main stats)
-        let month_start_date = today.with_day(1).unwrap();
-        let month_start = chrono::Local.from_local_datetime(&month_start_date.and_hms_opt(0, 0, 0).unwrap()).unwrap().with_timezone(&chrono::Utc);
+/// Counted commits of the month on a weekday × hour grid, shaded relative to the busiest hour
+struct PunchcardRenderer {
+    glyphs: &'static Glyphs,
+    palette: Palette,
+    week_start: WeekStart,
+    timezone: Tz,
+}
 
-        // Fetch all commits for the month period in a single API call for efficiency
-        let month_start_str = month_start.format("%Y-%m-%dT%H:%M:%SZ").to_string();
-        let today_end_str = today_end.format("%Y-%m-%dT%H:%M:%SZ").to_string();
-        let commits = self.get_commits_with_dates(full_repo_name, &month_start_str, &today_end_str).await?;
+const PUNCHCARD_HOUR_LABEL_STEP: usize = 3;
 
-        // Count commits by filtering in memory (more efficient than separate API calls)
-        let mut today_count = 0;
-        let mut week_count = 0;
-        let month_count = commits.len() as u32;
+impl Renderer for PunchcardRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        let Some(punchcard) = stats.punchcard.as_ref().filter(|punchcard| punchcard.total() > 0) else {
+            writeln!(out, "{}", "No commit times to show: the punch card is built from this month's commits in the scanned repositories, which need a token.".bright_yellow())?;
+            return Ok(());
+        };
+        let max = punchcard.counts.iter().flatten().copied().max().unwrap_or(0);
 
-        for commit in &commits {
-            if let Some(commit_date) = commit.get("commit")
-                .and_then(|c| c.get("author"))
-                .and_then(|a| a.get("date"))
-                .and_then(|d| d.as_str())
-                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-            {
-                let commit_date = commit_date.with_timezone(&chrono::Utc);
+        writeln!(out, "{}", format!("Commits by hour for {} ({} this month, {})", stats.username, punchcard.total(), self.timezone.name()).bright_cyan().bold())?;
+        let mut header = " ".repeat(DAY_LABEL_WIDTH);
+        for hour in (0..24).step_by(PUNCHCARD_HOUR_LABEL_STEP) {
+            header.push_str(&format!(" {:<width$}", hour, width = PUNCHCARD_HOUR_LABEL_STEP * CELL_WIDTH - 1));
+        }
+        writeln!(out, "{}", header.trim_end().bright_black())?;
 
-                if commit_date >= today_start && commit_date <= today_end {
-                    today_count += 1;
-                }
-                if commit_date >= week_start && commit_date <= today_end {
-                    week_count += 1;
-                }
+        let mut weekday = self.week_start.weekday();
+        for _ in 0..7 {
+            write!(out, "{:<width$}", weekday.to_string(), width = DAY_LABEL_WIDTH)?;
+            for &count in &punchcard.counts[weekday.num_days_from_monday() as usize] {
+                let level = if max == 0 { 0 } else { (count * 4).div_ceil(max) as u8 };
+                write!(out, " {}", level_symbol(level, self.glyphs, &self.palette))?;
+            }
+            writeln!(out)?;
+            weekday = weekday.succ();
+        }
+        writeln!(out)?;
+        writeln!(out, "{}", format!("Busiest hour: {} commits; commits are dated by their author", max).bright_black())?;
+        Ok(())
+    }
+}
+
+/// Month-by-month calendar blocks (Sunday to Saturday columns), a few months per row
+struct CalendarRenderer {
+    glyphs: &'static Glyphs,
+    palette: Palette,
+}
+
+const CALENDAR_MONTHS_PER_ROW: usize = 3;
+const CALENDAR_BLOCK_WIDTH: usize = 7 * CELL_WIDTH;
+
+impl CalendarRenderer {
+    /// Lines of one month block, each exactly `CALENDAR_BLOCK_WIDTH` columns wide
+    fn month_block(&self, first_of_month: NaiveDate, days: &HashMap<NaiveDate, &Day>) -> Vec<String> {
+        let title = first_of_month.format("%B %Y").to_string();
+        let mut lines = vec![
+            format!("{:^width$}", title, width = CALENDAR_BLOCK_WIDTH).bright_cyan().bold().to_string(),
+            format!("{:<width$}", "Su Mo Tu We Th Fr Sa", width = CALENDAR_BLOCK_WIDTH).bright_black().to_string(),
+        ];
+
+        // Blank cells before the 1st and after the last day keep partial weeks aligned
+        let leading = first_of_month.weekday().num_days_from_sunday() as usize;
+        let mut row = "   ".repeat(leading);
+        let mut cells = leading;
+        for date in first_of_month.iter_days().take_while(|date| date.month() == first_of_month.month()) {
+            match days.get(&date) {
+                Some(day) => row.push_str(&format!("{} ", level_symbol(day.level, self.glyphs, &self.palette))),
+                None => row.push_str("   "),
+            }
+            cells += 1;
+            if cells == 7 {
+                lines.push(std::mem::take(&mut row));
+                cells = 0;
+            }
+        }
+        if cells > 0 {
+            row.push_str(&"   ".repeat(7 - cells));
+            lines.push(row);
+        }
+        lines
+    }
+}
+
+impl Renderer for CalendarRenderer {
+    fn render(&self, stats: &Stats, out: &mut dyn Write) -> Result<()> {
+        let days: HashMap<NaiveDate, &Day> = stats.contribution_graph.weeks.iter()
+            .flat_map(|week| week.days.iter())
+            .filter_map(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok().map(|date| (date, day)))
+            .collect();
+        let (Some(&first), Some(&last)) = (days.keys().min(), days.keys().max()) else {
+            writeln!(out, "No contribution data")?;
+            return Ok(());
+        };
+
+        let mut months = Vec::new();
+        let mut month = first.with_day(1).unwrap_or(first);
+        while month <= last {
+            months.push(self.month_block(month, &days));
+            month = month.checked_add_months(chrono::Months::new(1)).unwrap_or(NaiveDate::MAX);
+        }
+
+        for row in months.chunks(CALENDAR_MONTHS_PER_ROW) {
+            writeln!(out)?;
+            let height = row.iter().map(Vec::len).max().unwrap_or(0);
+            for line in 0..height {
+                let parts: Vec<String> = row.iter()
+                    .map(|block| block.get(line).cloned().unwrap_or_else(|| " ".repeat(CALENDAR_BLOCK_WIDTH)))
+                    .collect();
+                writeln!(out, "{}", parts.join("  ").trim_end())?;
             }
         }
 
-        Ok((today_count, week_count, month_count))
+        writeln!(out)?;
+        let legend: Vec<String> = (0..=4).map(|level| level_symbol(level, self.glyphs, &self.palette).to_string()).collect();
+        writeln!(out, "{} {} {}", self.palette.legend[0], legend.join(" "), self.palette.legend[1])?;
+        Ok(())
     }
+}
 
-    /// Fetch commits from GitHub API with pagination, filtered by author
-    async fn get_commits_with_dates(&self, full_repo_name: &str, since: &str, until: &str) -> Result<Vec<serde_json::Value>> {
-        let mut all_commits = Vec::new();
-        let mut page = 1;
-        let per_page = 100;
+type RendererFactory = fn(&RenderContext) -> Box<dyn Renderer>;
 
-        loop {
-            let url = format!(
-                "https://api.github.com/repos/{}/commits?since={}&until={}&page={}&per_page={}",
-                full_repo_name, since, until, page, per_page
-            );
+/// Every `--format`, keyed by name
+const RENDERERS: &[(&str, RendererFactory)] = &[
+    ("text", |context| Box::new(TextRenderer { options: context.display, full_width: context.to_file })),
+    ("ansi-text", |context| Box::new(AnsiTextRenderer { options: context.display })),
+    ("json", |_| Box::new(JsonRenderer)),
+    ("github-json", |_| Box::new(GithubJsonRenderer)),
+    ("calendar", |context| Box::new(CalendarRenderer { glyphs: glyphs(context.display.ascii), palette: context.display.palette })),
+    ("svg", |context| Box::new(SvgRenderer {
+        theme: context.theme,
+        responsive: context.svg_responsive,
+        week_start: context.display.grid_week_start(),
+    })),
+    ("total", |_| Box::new(TotalRenderer)),
+    ("badge", |_| Box::new(BadgeRenderer)),
+    ("punchcard", |context| Box::new(PunchcardRenderer {
+        glyphs: glyphs(context.display.ascii),
+        palette: context.display.palette,
+        week_start: context.display.grid_week_start(),
+        timezone: context.display.timezone,
+    })),
+    ("table", |context| Box::new(TableRenderer { options: context.display })),
+    ("csv", |_| Box::new(CsvRenderer)),
+    ("markdown", |context| Box::new(MarkdownRenderer { options: context.display })),
+    ("weekly-csv", |context| Box::new(WeeklyCsvRenderer { active_threshold: context.display.active_threshold })),
+    ("html", |context| Box::new(HtmlRenderer { theme: context.theme, week_start: context.display.grid_week_start() })),
+];
 
-            match self.client.get(&url).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        let commits: Vec<serde_json::Value> = response.json().await?;
-                        if commits.is_empty() {
-                            break;
-                        }
+/// Formats implied by `--output` file extensions
+const OUTPUT_EXTENSIONS: &[(&str, &str)] = &[
+    ("svg", "svg"),
+    ("json", "json"),
+    ("txt", "text"),
+    ("ans", "ansi-text"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("png", "png"),
+    ("csv", "csv"),
+    ("md", "markdown"),
+];
 
-                        // Filter commits by GitHub author (not Git author) for accuracy
-                        let user_commits: Vec<serde_json::Value> = commits.into_iter().filter(|commit| {
-                            if let Some(author) = commit.get("author") {
-                                if let Some(login) = author.get("login") {
-                                    return login.as_str() == Some(&self.username);
-                                }
-                            }
-                            false
-                        }).collect();
+/// Pick the output format: an explicit `--format` wins, then the `--output` extension, then text.
+/// Errors when the extension implies a format that has no renderer.
+fn resolve_format(format: Option<&str>, output: Option<&std::path::Path>) -> Result<String> {
+    if let Some(format) = format {
+        return Ok(format.to_string());
+    }
 
-                        all_commits.extend(user_commits);
-                        page += 1;
+    let extension = output
+        .and_then(|path| path.extension())
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    let Some(extension) = extension else {
+        return Ok("text".to_string());
+    };
 
-                        // Limit pagination to avoid rate limits
-                        if page > 5 {
-                            break;
-                        }
-                    } else {
-                        break;
-                    }
-                }
-                Err(_) => break,
+    match OUTPUT_EXTENSIONS.iter().find(|(ext, _)| *ext == extension) {
+        Some((_, format)) if RENDERERS.iter().any(|(name, _)| name == format) => Ok(format.to_string()),
+        Some((_, format)) => anyhow::bail!(
+            "--output .{} files would be written as '{}', which isn't supported; pass --format explicitly",
+            extension, format
+        ),
+        None => Ok("text".to_string()),
+    }
+}
+
+fn find_renderer(format: &str, context: &RenderContext) -> Result<Box<dyn Renderer>> {
+    match RENDERERS.iter().find(|(name, _)| *name == format) {
+        Some((_, factory)) => Ok(factory(context)),
+        None => {
+            let names: Vec<&str> = RENDERERS.iter().map(|(name, _)| *name).collect();
+            anyhow::bail!("Unknown format '{}' (available: {})", format, names.join(", "))
+        }
+    }
+}
+
+const SVG_CELL_SIZE: usize = 10;
+const SVG_CELL_GAP: usize = 3;
+const SVG_PADDING: usize = 10;
+const SVG_FONT_SIZE: usize = 9;
+/// Room left of the grid for the weekday labels
+const SVG_DAY_LABEL_WIDTH: usize = 27;
+/// Room above the grid for the month labels
+const SVG_MONTH_LABEL_HEIGHT: usize = 15;
+/// A month label needs about this many columns, so one closer to the next is dropped
+const SVG_MIN_MONTH_LABEL_WEEKS: usize = 3;
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn svg_theme_rules(theme: &Theme) -> String {
+    let mut rules = format!(".bg{{fill:{}}}.label{{fill:{}}}", theme.background.hex(), GRAPH_LABEL_COLOR);
+    for (level, color) in theme.levels.iter().enumerate() {
+        rules.push_str(&format!(".l{}{{fill:{}}}", level, color.hex()));
+    }
+    rules
+}
+
+/// Render the contribution graph as a standalone SVG document.
+/// Cells are styled through classes so a responsive SVG can swap the palette with a media query.
+/// Rows run from `week_start`, regrouping the weeks like the text grid does.
+fn render_svg(stats: &Stats, theme: &Theme, responsive: bool, week_start: WeekStart) -> Result<String> {
+    let step = SVG_CELL_SIZE + SVG_CELL_GAP;
+    let weeks = grid_weeks(&stats.contribution_graph.weeks, week_start);
+    let width = SVG_PADDING * 2 + SVG_DAY_LABEL_WIDTH + weeks.len() * step;
+    let height = SVG_PADDING * 2 + SVG_MONTH_LABEL_HEIGHT + 7 * step;
+    let grid_x = SVG_PADDING + SVG_DAY_LABEL_WIDTH;
+    let grid_y = SVG_PADDING + SVG_MONTH_LABEL_HEIGHT;
+
+    let style = if responsive {
+        format!(
+            "{}@media (prefers-color-scheme: dark){{{}}}",
+            svg_theme_rules(find_theme("light")?),
+            svg_theme_rules(find_theme("dark")?)
+        )
+    } else {
+        svg_theme_rules(theme)
+    };
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    ));
+    svg.push_str(&format!("<title>GitHub contributions of {}</title>\n", xml_escape(&stats.username)));
+    svg.push_str(&format!("<style>{}</style>\n", style));
+    svg.push_str(&format!("<rect class=\"bg\" width=\"{}\" height=\"{}\"/>\n", width, height));
+    svg.push_str(&format!("<g class=\"label\" font-family=\"sans-serif\" font-size=\"{}\">\n", SVG_FONT_SIZE));
+
+    // A label over each week that starts a new month, like the terminal header
+    let week_starts: Vec<Option<NaiveDate>> = weeks.iter()
+        .map(|week| week.days.first().and_then(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()))
+        .collect();
+    let mut month_labels: Vec<(usize, NaiveDate)> = Vec::new();
+    let mut previous_month = None;
+    for (week_idx, start) in week_starts.iter().enumerate() {
+        let Some(start) = start else { continue };
+        if previous_month == Some(start.month()) {
+            continue;
+        }
+        previous_month = Some(start.month());
+        // A partial first month would crowd the next label; the newer month wins
+        if month_labels.last().is_some_and(|&(last_idx, _)| week_idx - last_idx < SVG_MIN_MONTH_LABEL_WEEKS) {
+            month_labels.pop();
+        }
+        month_labels.push((week_idx, *start));
+    }
+    for (week_idx, start) in month_labels {
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\">{}</text>\n",
+            grid_x + week_idx * step,
+            SVG_PADDING + SVG_FONT_SIZE,
+            start.format("%b")
+        ));
+    }
+    for (day_of_week, label) in week_start.day_labels().into_iter().enumerate().filter(|(_, label)| !label.is_empty()) {
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\">{}</text>\n",
+            SVG_PADDING,
+            grid_y + day_of_week * step + SVG_CELL_SIZE - 1,
+            label
+        ));
+    }
+    svg.push_str("</g>\n");
+
+    for (week_idx, (week, start)) in weeks.iter().zip(&week_starts).enumerate() {
+        // A week cut short by the start of the window begins on a later row
+        let offset = start.map_or(0, |date| week_start.row(date));
+        for (index, day) in week.days.iter().enumerate() {
+            svg.push_str(&format!(
+                "<rect class=\"l{}\" x=\"{}\" y=\"{}\" width=\"{size}\" height=\"{size}\" rx=\"2\"><title>{}: {} contributions</title></rect>\n",
+                day.level.min(4),
+                grid_x + week_idx * step,
+                grid_y + (offset + index).min(6) * step,
+                xml_escape(&day.date),
+                day.count,
+                size = SVG_CELL_SIZE
+            ));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// Year-end total if the rest of the year keeps the pace so far, or None during
+/// the first couple of weeks when a handful of days would skew it
+fn project_year_total(year_to_date: u32, today: NaiveDate) -> Option<u32> {
+    let elapsed = today.ordinal();
+    if elapsed < MIN_PROJECTION_DAYS {
+        return None;
+    }
+    let days_in_year = if today.leap_year() { 366 } else { 365 };
+    Some((year_to_date as u64 * days_in_year / elapsed as u64) as u32)
+}
+
+/// Contributions in one ISO week (Monday to Sunday)
+#[derive(Debug, Default)]
+struct WeeklyTotal {
+    iso_year: i32,
+    iso_week: u32,
+    total: u32,
+    active_days: u32,
+}
+
+/// Group the days by ISO week, oldest first. The graph's weeks run Sunday to Saturday, so
+/// they're regrouped by date; early-January days can belong to the previous ISO year
+/// (and late-December ones to the next), which `iso_week()` accounts for.
+fn weekly_totals(graph: &ContributionGraph, active_threshold: u32) -> Vec<WeeklyTotal> {
+    let mut weeks: std::collections::BTreeMap<(i32, u32), WeeklyTotal> = std::collections::BTreeMap::new();
+    for day in graph.weeks.iter().flat_map(|week| &week.days) {
+        let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let iso = date.iso_week();
+        let week = weeks.entry((iso.year(), iso.week())).or_insert_with(|| WeeklyTotal {
+            iso_year: iso.year(),
+            iso_week: iso.week(),
+            ..Default::default()
+        });
+        week.total += day.count;
+        if is_active(day, active_threshold) {
+            week.active_days += 1;
+        }
+    }
+    weeks.into_values().collect()
+}
+
+/// Days and contributions that fall into one contribution level
+#[derive(Debug, Default, Clone, Copy)]
+struct LevelBucket {
+    days: u32,
+    contributions: u32,
+}
+
+/// How the graph's days and contributions are spread over levels 0-4
+fn level_distribution(graph: &ContributionGraph) -> [LevelBucket; 5] {
+    let mut buckets = [LevelBucket::default(); 5];
+    for day in graph.weeks.iter().flat_map(|week| &week.days) {
+        let bucket = &mut buckets[(day.level as usize).min(4)];
+        bucket.days += 1;
+        bucket.contributions += day.count;
+    }
+    buckets
+}
+
+const HTML_CELL_SIZE: usize = 10;
+const HTML_CELL_GAP: usize = 3;
+const GRAPH_LABEL_COLOR: &str = "#7d8590"; // readable on both the light and dark backgrounds
+
+/// Render the contribution graph as a self-contained HTML fragment (a table with inline CSS),
+/// for embedding in a page. Every cell has a title tooltip with its date and count.
+/// Rows run from `week_start`, regrouping the weeks like the text grid does.
+fn render_html(stats: &Stats, theme: &Theme, week_start: WeekStart) -> String {
+    let mut style = format!(
+        ".gh-activity{{background:{bg};color:{label};font:9px sans-serif;padding:10px;display:inline-block}}\
+         .gh-activity table{{border-collapse:separate;border-spacing:{gap}px;table-layout:fixed}}\
+         .gh-activity th{{font-weight:normal;text-align:left;white-space:nowrap;overflow:visible;width:{size}px;height:{size}px;padding:0}}\
+         .gh-activity th.day{{width:auto;padding-right:4px;text-align:right}}\
+         .gh-activity td{{width:{size}px;height:{size}px;padding:0;border-radius:2px}}",
+        bg = theme.background.hex(),
+        label = GRAPH_LABEL_COLOR,
+        gap = HTML_CELL_GAP,
+        size = HTML_CELL_SIZE
+    );
+    for (level, color) in theme.levels.iter().enumerate() {
+        style.push_str(&format!(".gh-activity .l{}{{background:{}}}", level, color.hex()));
+    }
+
+    let weeks = grid_weeks(&stats.contribution_graph.weeks, week_start);
+    let mut html = String::new();
+    html.push_str(&format!(
+        "<div class=\"gh-activity\" title=\"GitHub contributions of {}\">\n",
+        xml_escape(&stats.username)
+    ));
+    html.push_str(&format!("<style>{}</style>\n", style));
+    html.push_str("<table>\n<tr><th class=\"day\"></th>");
+
+    // Label a column when its week starts a new month
+    let mut previous_month = None;
+    for week in weeks.iter() {
+        let month = week.days.first()
+            .and_then(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok())
+            .map(|date| date.format("%b").to_string());
+        if month.is_some() && month != previous_month {
+            html.push_str(&format!("<th>{}</th>", month.as_deref().unwrap_or_default()));
+        } else {
+            html.push_str("<th></th>");
+        }
+        previous_month = month;
+    }
+    html.push_str("</tr>\n");
+
+    // A week cut short by the start of the window begins on a later row
+    let offsets: Vec<usize> = weeks.iter()
+        .map(|week| {
+            week.days.first()
+                .and_then(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok())
+                .map_or(0, |start| week_start.row(start))
+        })
+        .collect();
+    for (day_of_week, label) in week_start.day_labels().iter().enumerate() {
+        html.push_str(&format!("<tr><th class=\"day\">{}</th>", label));
+        for (week, offset) in weeks.iter().zip(&offsets) {
+            match day_of_week.checked_sub(*offset).and_then(|index| week.days.get(index)) {
+                Some(day) => html.push_str(&format!(
+                    "<td class=\"l{}\" title=\"{}: {} contributions\"></td>",
+                    day.level.min(4),
+                    xml_escape(&day.date),
+                    day.count
+                )),
+                None => html.push_str("<td></td>"),
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n</div>\n");
+    html
+}
+
+/// Whether a day counts as active: at least `threshold` contributions (and always at least one)
+fn is_active(day: &Day, threshold: u32) -> bool {
+    day.count >= threshold.max(1)
+}
+
+/// Saturday or Sunday; a day whose date doesn't parse counts as a weekday
+fn is_weekend(day: &Day) -> bool {
+    NaiveDate::parse_from_str(&day.date, "%Y-%m-%d")
+        .is_ok_and(|date| matches!(date.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun))
+}
+
+/// The graph's days, oldest first, without weekends when `weekdays_only` is set
+fn counted_days(graph: &ContributionGraph, weekdays_only: bool) -> impl Iterator<Item = &Day> {
+    graph.weeks.iter()
+        .flat_map(|week| &week.days)
+        .filter(move |day| !(weekdays_only && is_weekend(day)))
+}
+
+const WEEKDAY_NAMES: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// The weekday with the most contributions per day, the earliest in the week on a tie.
+/// Only days up to today count toward the averages; None when there's nothing to compare.
+fn most_active_weekday(graph: &ContributionGraph, weekdays_only: bool, zone: Tz) -> Option<WeekdayActivity> {
+    let today = today_in(zone);
+    // Indexed by days from Monday, like WEEKDAY_NAMES
+    let mut totals = [(0u32, 0u32); 7];
+    for day in counted_days(graph, weekdays_only) {
+        if let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") {
+            if date <= today {
+                let (total, days) = &mut totals[date.weekday().num_days_from_monday() as usize];
+                *total += day.count;
+                *days += 1;
             }
         }
+    }
+
+    let mut best: Option<(usize, f64)> = None;
+    for (index, &(total, days)) in totals.iter().enumerate() {
+        if total == 0 {
+            continue;
+        }
+        let average = total as f64 / days as f64;
+        if best.is_none_or(|(_, best_average)| average > best_average) {
+            best = Some((index, average));
+        }
+    }
+    best.map(|(index, average)| WeekdayActivity {
+        weekday: WEEKDAY_NAMES[index].to_string(),
+        total: totals[index].0,
+        average,
+    })
+}
+
+/// Current and longest run of days with contributions. With `weekdays_only`, weekends
+/// neither extend nor break a streak.
+/// The last day of the calendar is still in progress, so an empty last day doesn't break the current streak.
+fn compute_streaks(graph: &ContributionGraph, threshold: u32, weekdays_only: bool) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    for day in counted_days(graph, weekdays_only) {
+        run = if is_active(day, threshold) { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+
+    (current_streak_days(graph, threshold, weekdays_only).len() as u32, longest)
+}
+
+/// The days of the current streak, oldest first. Today not being active yet doesn't
+/// break it; a streak reaching past the start of the graph is cut off at the first day shown.
+fn current_streak_days(graph: &ContributionGraph, threshold: u32, weekdays_only: bool) -> Vec<&Day> {
+    let days: Vec<&Day> = counted_days(graph, weekdays_only).collect();
+    let settled = match days.last() {
+        Some(day) if !is_active(day, threshold) => &days[..days.len() - 1],
+        _ => &days[..],
+    };
+    let length = settled.iter().rev().take_while(|day| is_active(day, threshold)).count();
+    settled[settled.len() - length..].to_vec()
+}
+
+/// Per-period totals derived from the calendar, shared by the text footer and `--summary-json`
+#[derive(Debug, Serialize)]
+struct SummaryStats {
+    today: u32,
+    this_week: u32,
+    last_week: u32,
+    this_month: u32,
+    this_year: u32,
+    /// This week minus last week
+    week_delta: i64,
+    /// Days with at least the `--active-threshold` number of contributions
+    active_days: u32,
+    /// Days in the graph up to today
+    total_days: u32,
+    /// Share of days that were active, from 0 to 1
+    consistency: f64,
+    month_active_days: u32,
+    /// Days of the current month so far
+    month_days: u32,
+    current_streak: u32,
+    longest_streak: u32,
+}
 
-        Ok(all_commits)
-    }
+/// With `weekdays_only`, Saturdays and Sundays are left out of every figure
+fn compute_summary(graph: &ContributionGraph, active_threshold: u32, weekdays_only: bool, week_start: WeekStart, zone: Tz) -> SummaryStats {
+    let today = today_in(zone);
+    let this_week_start = week_start.week_of(today);
+    let last_week_start = this_week_start - chrono::Duration::days(7);
+    let last_week_end = this_week_start - chrono::Duration::days(1);
+    let this_month_start = first_of_month(today);
+    let this_year_start = first_of_year(today);
     
-    async fn generate_data(&self) -> Result<(ContributionGraph, Vec<RepositoryWithCommits>)> {
-        match self.get_data_from_graphql().await {
-            Ok((graph, repos)) => Ok((graph, repos)),
-            Err(_) => {
-                Ok((
-                    ContributionGraph {
-                        weeks: Vec::new(),
-                        total_contributions: 0,
-                    },
-                    Vec::new()
-                ))
+    let mut summary = SummaryStats {
+        today: 0,
+        this_week: 0,
+        last_week: 0,
+        this_month: 0,
+        this_year: 0,
+        week_delta: 0,
+        active_days: 0,
+        total_days: 0,
+        consistency: 0.0,
+        month_active_days: 0,
+        month_days: 0,
+        current_streak: 0,
+        longest_streak: 0,
+    };
+    
+    for day in counted_days(graph, weekdays_only) {
+        let active = is_active(day, active_threshold);
+        if let Ok(day_date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") {
+            if day_date <= today {
+                summary.total_days += 1;
+                summary.active_days += active as u32;
+                if day_date >= this_month_start {
+                    summary.month_days += 1;
+                    summary.month_active_days += active as u32;
+                }
+            }
+            if day_date == today {
+                summary.today = day.count;
+            }
+            if day_date >= this_week_start {
+                summary.this_week += day.count;
+            }
+            if day_date >= last_week_start && day_date <= last_week_end {
+                summary.last_week += day.count;
+            }
+            if day_date >= this_month_start {
+                summary.this_month += day.count;
+            }
+            if day_date >= this_year_start {
+                summary.this_year += day.count;
             }
         }
     }
 
+    summary.week_delta = summary.this_week as i64 - summary.last_week as i64;
+    if summary.total_days > 0 {
+        summary.consistency = summary.active_days as f64 / summary.total_days as f64;
+    }
+    (summary.current_streak, summary.longest_streak) = compute_streaks(graph, active_threshold, weekdays_only);
+    summary
+}
 
-    async fn get_stats(&self) -> Result<Stats> {
-        let user = self.get_user().await?;
-        let (contribution_graph, recent_repos) = self.generate_data().await?;
+fn load_stats(path: &std::path::Path) -> Result<Stats> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open snapshot {}", path.display()))?;
+    serde_json::from_reader(std::io::BufReader::new(file))
+        .with_context(|| format!("Failed to parse snapshot {}", path.display()))
+}
 
-        Ok(Stats {
-            username: user.login,
-            contribution_graph,
-            recent_repos,
-        })
+fn format_delta(old: u32, new: u32) -> ColoredString {
+    let delta = new as i64 - old as i64;
+    match delta.cmp(&0) {
+        std::cmp::Ordering::Greater => format!("+{}", delta).bright_green(),
+        std::cmp::Ordering::Less => delta.to_string().bright_red(),
+        std::cmp::Ordering::Equal => "same".bright_black(),
     }
 }
 
-async fn show_loading_animation() {
-    let mut frame_idx = 0;
-    let placeholder = "⬜".bright_black();
-    
-    // Print the loading graph once - same dimensions as contribution graph
+/// Report what changed between two snapshots of the same user
+fn display_snapshot_diff(old: &Stats, new: &Stats, active_threshold: u32, weekdays_only: bool) {
     println!();
-    println!("       Sep          Oct          Nov          Dec          Jan          Feb          Mar          Apr          May          Jun          Jul          Aug");
-    
-    // Print graph rows
-    for (i, day_label) in std::iter::once("       ").chain(DAY_LABELS.iter().copied()).enumerate() {
-        if i == 0 {
-            print!("{}", day_label);
-        } else {
-            print!("{} ", day_label);
-        }
-        
-        for _ in 0..WEEKS_IN_YEAR {
-            print!("{} ", placeholder);
+    println!("{}", format!("Changes for {}", new.username).bright_cyan().bold());
+    if !old.username.eq_ignore_ascii_case(&new.username) {
+        println!("{}", format!("Note: comparing different users ({} → {})", old.username, new.username).bright_yellow());
+    }
+    println!();
+
+    let old_total = old.contribution_graph.total_contributions;
+    let new_total = new.contribution_graph.total_contributions;
+    println!("Total contributions: {} → {} ({})", old_total, new_total, format_delta(old_total, new_total));
+
+    let (old_current, old_longest) = compute_streaks(&old.contribution_graph, active_threshold, weekdays_only);
+    let (new_current, new_longest) = compute_streaks(&new.contribution_graph, active_threshold, weekdays_only);
+    println!("Current streak: {} → {} days ({})", old_current, new_current, format_delta(old_current, new_current));
+    println!("Longest streak: {} → {} days ({})", old_longest, new_longest, format_delta(old_longest, new_longest));
+
+    let mut gained = Vec::new();
+    let mut added = Vec::new();
+    for repo in &new.recent_repos {
+        match old.recent_repos.iter().find(|old_repo| old_repo.full_name == repo.full_name) {
+            Some(old_repo) if repo.month_commits > old_repo.month_commits => gained.push((repo, old_repo.month_commits)),
+            Some(_) => {}
+            None => added.push(repo),
         }
+    }
+
+    if !gained.is_empty() {
         println!();
+        println!("{}", "Repositories with new commits this month:".bright_white().bold());
+        for (repo, old_commits) in gained {
+            println!("  {:<35} {} → {} ({})",
+                repo.full_name.bright_blue(),
+                old_commits,
+                repo.month_commits,
+                format_delta(old_commits, repo.month_commits)
+            );
+        }
     }
-    
-    println!();
-    print!("Loading {} contributions...", SPINNER_FRAMES[0].bright_blue());
-    stdout().flush().unwrap();
-    
-    // Animate only the spinner
-    loop {
-        print!("\rLoading {} contributions...", SPINNER_FRAMES[frame_idx].bright_blue());
-        stdout().flush().unwrap();
-        
-        frame_idx = (frame_idx + 1) % SPINNER_FRAMES.len();
-        tokio::time::sleep(Duration::from_millis(150)).await;
+
+    if !added.is_empty() {
+        println!();
+        println!("{}", "New repositories:".bright_white().bold());
+        for repo in added {
+            println!("  {:<35} {} commits this month", repo.full_name.bright_blue(), repo.month_commits);
+        }
     }
 }
 
-fn display_contribution_graph(stats: &Stats) {
-    println!();
-    
-    // Display month labels
-    print!("       ");
-    let months = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
-    let total_weeks = stats.contribution_graph.weeks.len();
-    
-    if total_weeks > 0 {
-        let current_month = Utc::now().month0() as usize;
-        let start_month = (current_month + 1) % 12;
-        
-        let spacing = MONTH_SPACING;
-        
-        for i in 0..12 {
-            if i > 0 {
-                for _ in 0..spacing {
-                    print!(" ");
-                }
-            }
-            
-            let month_idx = (start_month + i) % 12;
-            print!("{}", months[month_idx]);
-        }
+/// Prepare the console for emoji and ANSI colors. Returns false when UTF-8 output
+/// can't be enabled, in which case the graph is drawn with ASCII instead.
+#[cfg(windows)]
+fn setup_console() -> bool {
+    use windows_sys::Win32::System::Console::SetConsoleOutputCP;
+    const CP_UTF8: u32 = 65001;
+
+    // Older consoles need virtual terminal processing switched on for ANSI colors
+    colored::control::set_virtual_terminal(true).ok();
+
+    // SAFETY: plain Win32 call without pointers, it only changes this console's output code page
+    unsafe { SetConsoleOutputCP(CP_UTF8) != 0 }
+}
+
+#[cfg(not(windows))]
+fn setup_console() -> bool {
+    true
+}
+
+/// Keep the text graph on screen, panning with ←/→, until 'q', Esc or Ctrl+C
+async fn run_interactive(mut stats: Stats, options: &DisplayOptions, watch: Option<&Watch<'_>>) -> Result<()> {
+    let mut view = GraphView::fit(grid_week_count(&stats.contribution_graph, options), options);
+
+    // The graph is already out, so an unsupported terminal just ends the run here
+    if !supports_raw_mode() {
+        eprintln!();
+        eprintln!("{}", "Interactive mode isn't available in this terminal, exiting.".bright_black());
+        return Ok(());
     }
-    println!();
+    let mut status = watch.map(|watch| format!("Refreshing every {}s", watch.every.as_secs()));
+    print_exit_hint(view, status.as_deref());
     
-    // Display day labels and contribution graph
-    let day_labels = ["", "Mon", "", "Wed", "", "Fri", ""];
+    // Enable raw mode for key detection
+    terminal::enable_raw_mode()?;
     
-    for day_of_week in 0..7 {
-        print!("{:>6}", day_labels[day_of_week]);
-        
-        for week in &stats.contribution_graph.weeks {
-            if let Some(day) = week.days.get(day_of_week) {
-                let symbol = match day.level {
-                    0 => "⬛".bright_black(),
-                    1 => "🟩".bright_green(), 
-                    2 => "🟨".bright_yellow(),
-                    3 => "🟧".bright_yellow(), 
-                    4 => "🟥".bright_red(),
-                    _ => "⬛".bright_black(),
-                };
-                print!(" {}", symbol);
-            } else {
-                print!(" ⬛");
+    // Keep the process running and listen for key presses
+    let mut next_refresh = watch.map(|watch| std::time::Instant::now() + watch.every);
+    loop {
+        if let (Some(watch), Some(due)) = (watch, next_refresh) {
+            if std::time::Instant::now() >= due {
+                // Out of raw mode, Ctrl+C is a signal again and can interrupt a slow fetch
+                terminal::disable_raw_mode()?;
+                let now = Utc::now().with_timezone(&options.timezone).format("%H:%M:%S");
+                status = Some(match watch.refresh().await {
+                    Ok(fresh) => {
+                        stats = fresh;
+                        view = GraphView::fit(grid_week_count(&stats.contribution_graph, options), options);
+                        format!("Updated at {}, refreshing every {}s", now, watch.every.as_secs())
+                    }
+                    Err(e) => format!("Refresh at {} failed, showing the previous data: {}", now, e),
+                });
+                print!("\x1b[2J\x1b[1;1H");
+                display_contribution_graph(&stats, view, options, &mut stdout())?;
+                print_exit_hint(view, status.as_deref());
+                terminal::enable_raw_mode()?;
+                next_refresh = Some(std::time::Instant::now() + watch.every);
             }
         }
-        println!();
-    }
-    
-    println!();
-    
-    // Calculate additional stats
-    let today = chrono::Local::now().date_naive();
-    let this_week_start = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
-    let last_week_start = this_week_start - chrono::Duration::days(7);
-    let last_week_end = this_week_start - chrono::Duration::days(1);
-    let this_month_start = today.with_day(1).unwrap();
-    let this_year_start = today.with_ordinal(1).unwrap();
-    
-    let mut today_contributions = 0;
-    let mut this_week_contributions = 0;
-    let mut last_week_contributions = 0;
-    let mut this_month_contributions = 0;
-    let mut this_year_contributions = 0;
-    
-    for week in &stats.contribution_graph.weeks {
-        for day in &week.days {
-            if let Ok(day_date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") {
-                if day_date == today {
-                    today_contributions = day.count;
-                }
-                if day_date >= this_week_start {
-                    this_week_contributions += day.count;
-                }
-                if day_date >= last_week_start && day_date <= last_week_end {
-                    last_week_contributions += day.count;
-                }
-                if day_date >= this_month_start {
-                    this_month_contributions += day.count;
-                }
-                if day_date >= this_year_start {
-                    this_year_contributions += day.count;
+
+        if event::poll(Duration::from_millis(100))? {
+            if let CrosstermEvent::Key(key_event) = event::read()? {
+                if key_event.kind == KeyEventKind::Press {
+                    match key_event.code {
+                        KeyCode::Char('q') | KeyCode::Char('Q') => {
+                            break;
+                        }
+                        KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                            break;
+                        }
+                        KeyCode::Esc => {
+                            break;
+                        }
+                        KeyCode::Left | KeyCode::Right => {
+                            let moved = if key_event.code == KeyCode::Left {
+                                view.scroll_left()
+                            } else {
+                                view.scroll_right()
+                            };
+                            if moved {
+                                // Raw mode doesn't translate newlines, so leave it while redrawing
+                                terminal::disable_raw_mode()?;
+                                print!("\x1b[2J\x1b[1;1H");
+                                display_contribution_graph(&stats, view, options, &mut stdout())?;
+                                print_exit_hint(view, status.as_deref());
+                                terminal::enable_raw_mode()?;
+                            }
+                        }
+                        _ => {}
+                    }
                 }
             }
         }
     }
     
-    // Week comparison
-    let week_diff = this_week_contributions as i32 - last_week_contributions as i32;
-    let comparison = if week_diff > 0 {
-        format!(" ({} more than last week)", week_diff.to_string().bright_green())
-    } else if week_diff < 0 {
-        format!(" ({} less than last week)", (-week_diff).to_string().bright_red())
-    } else {
-        " (same as last week)".to_string()
-    };
-    
-    // Single line with all stats and comparison
-    println!("Today: {} | This week: {}{} | This month: {} | This year: {}", 
-        today_contributions.to_string().bright_green(),
-        this_week_contributions.to_string().bright_green(),
-        comparison,
-        this_month_contributions.to_string().bright_green(),
-        this_year_contributions.to_string().bright_green()
-    );
-    
-    // Legend
-    println!();
-    print!("Less ");
-    print!("{} ", "⬛".bright_black());
-    print!("{} ", "🟩".bright_green());
-    print!("{} ", "🟨".bright_yellow());
-    print!("{} ", "🟧".bright_yellow());
-    print!("{} ", "🟥".bright_red());
-    println!("More");
-
-    // Display latest updated repositories with commit counts
-    if !stats.recent_repos.is_empty() {
-        println!();
-        println!("{}", "Latest Updated Repositories:".bright_cyan().bold());
-        println!();
+    // Disable raw mode before exiting
+    terminal::disable_raw_mode()?;
+    Ok(())
+}
 
-        // Column headers with color coding
-        println!("{:<4} {:<35} {:<8} {:<10} {:<12} {:<15}",
-            "No.".bright_white().bold(),
-            "Repository".bright_white().bold(),
-            "Today".bright_green().bold(),
-            "This Week".bright_cyan().bold(),
-            "This Month".bright_yellow().bold(),
-            "Last Updated".bright_white().bold()
-        );
+/// Probe raw mode once; CI shells and restricted consoles refuse it
+fn supports_raw_mode() -> bool {
+    stdout().is_terminal()
+        && terminal::enable_raw_mode()
+            .and_then(|_| terminal::disable_raw_mode())
+            .is_ok()
+}
 
-        // Separator line
-        println!("{}", "─".repeat(85).bright_black());
-
-        for (i, repo) in stats.recent_repos.iter().take(5).enumerate() {
-            // Format the pushed_at time
-            let pushed_display = if let Ok(pushed_time) = chrono::DateTime::parse_from_rfc3339(&repo.pushed_at) {
-                let now = chrono::Utc::now();
-                let duration = now.signed_duration_since(pushed_time);
-
-                if duration.num_days() > 0 {
-                    format!("{} days ago", duration.num_days())
-                } else if duration.num_hours() > 0 {
-                    format!("{} hours ago", duration.num_hours())
-                } else if duration.num_minutes() > 0 {
-                    format!("{} minutes ago", duration.num_minutes())
-                } else {
-                    "just now".to_string()
-                }
-            } else {
-                "unknown".to_string()
-            };
+fn print_exit_hint(view: GraphView, status: Option<&str>) {
+    println!();
+    if let Some(status) = status {
+        println!("{}", status.bright_black());
+    }
+    if view.is_partial() {
+        println!("{}", "Use ←/→ to scroll, 'q' or Ctrl+C to exit".bright_black());
+    } else {
+        println!("{}", "Press 'q' or Ctrl+C to exit".bright_black());
+    }
+    stdout().flush().ok();
+}
 
-            let repo_name = if repo.is_private {
-                format!("{} ⛨", repo.full_name)
-            } else {
-                repo.full_name.clone()
-            };
+/// `--benchmark` report of the API footprint and the time each phase took
+fn print_benchmark(metrics: &RequestMetrics, fetch: Duration, render: Duration, total: Duration) {
+    eprintln!();
+    eprintln!("{}", "Benchmark:".bright_cyan().bold());
+    eprintln!("  Requests:          {} ({} GraphQL, {} REST)",
+        (metrics.graphql_requests + metrics.rest_requests).to_string().bright_green(),
+        metrics.graphql_requests,
+        metrics.rest_requests
+    );
+    eprintln!("  Received:          {:.1} KB", metrics.bytes as f64 / 1024.0);
+    eprintln!("  Waiting on GitHub: {:.2}s summed over requests", metrics.request_time.as_secs_f64());
+    eprintln!("  Rate-limit pauses: {:.2}s", metrics.rate_limit_wait.as_secs_f64());
+    eprintln!("  Fetch:             {:.2}s", fetch.as_secs_f64());
+    eprintln!("  Render:            {:.2}s", render.as_secs_f64());
+    eprintln!("  Total:             {:.2}s", total.as_secs_f64());
+}
 
-            println!("{:<4} {:<35} {:<8} {:<10} {:<12} {:<15}",
-                format!("{}.", i + 1).bright_white(),
-                repo_name.bright_blue().bold(),
-                repo.today_commits.to_string().bright_green(),
-                repo.week_commits.to_string().bright_cyan(),
-                repo.month_commits.to_string().bright_yellow(),
-                pushed_display.bright_black()
-            );
-        }
+/// API endpoints picked by --base-url, or else --api-url
+fn cli_endpoints(cli: &Cli) -> ApiEndpoints {
+    match &cli.base_url {
+        Some(host) => ApiEndpoints::for_host(host, cli.graphql_url.as_deref()),
+        None => ApiEndpoints::new(&cli.api_url, cli.graphql_url.as_deref()),
     }
 }
 
-
+/// Client options for runs that only need each user's calendar, skipping the per-repo commit counting
+fn calendar_only_options(cli: &Cli, window: Option<DateWindow>, timezone: Tz) -> ClientOptions {
+    ClientOptions {
+        max_requests: cli.max_requests,
+        retry_budget: cli.max_total_retry_time,
+        timeout: Duration::from_secs(cli.timeout),
+        endpoints: cli_endpoints(cli),
+        gh_fallback: !cli.no_gh_fallback,
+        max_pages: cli.max_pages,
+        repo_count: cli.repos as usize,
+        scan_repos: 0,
+        public_only: cli.public_only,
+        window,
+        verbose: cli.verbose,
+        graphql_extra: None,
+        pinned: false,
+        list_commits: false,
+        punchcard: false,
+        all_authors: false,
+        dedupe_forks: false,
+        week_start: cli.week_start.unwrap_or(DEFAULT_PERIOD_WEEK_START),
+        timezone,
+        strict: cli.strict,
+        repo_filter: None,
+        language: None,
+        source: cli.source,
+    }
+}
 
 #[tokio::main] 
 async fn main() -> Result<()> {
+    let started = std::time::Instant::now();
     let cli = Cli::parse();
+    let ascii = !setup_console() || cli.ascii;
+    // colored already checks NO_COLOR, but the 256-color escapes and the depth need to know too
+    let no_color = cli.no_color || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if no_color {
+        colored::control::set_override(false);
+    }
+
+    if cli.list_themes {
+        list_themes();
+        return Ok(());
+    }
+
+    // Snapshot comparison works offline, no user lookup or client needed
+    if let Some(paths) = &cli.diff {
+        let old = load_stats(&paths[0])?;
+        let new = load_stats(&paths[1])?;
+        display_snapshot_diff(&old, &new, cli.active_threshold, cli.weekdays_only);
+        return Ok(());
+    }
+
+    let timezone = resolve_timezone(cli.timezone.as_deref())?;
+    let window = match (cli.year, cli.from) {
+        (Some(year), _) => Some(DateWindow::year(year, timezone)?),
+        (None, Some(from)) => Some(DateWindow::between(from, cli.to, timezone)?),
+        (None, None) => cli.since_days.map(|days| DateWindow::since_days(days, timezone)),
+    };
+
+    if cli.leaderboard || cli.org_members.is_some() {
+        let options = calendar_only_options(&cli, window, timezone);
+        let client = GitHubClient::new(String::new(), cli.token, options)
+            .context("Failed to create GitHub client")?;
+        let users = match (&cli.org_members, &cli.users_file) {
+            (Some(org), _) => client.get_org_members(org).await?,
+            (None, Some(path)) => read_users_file(path)?,
+            (None, None) => Vec::new(),
+        };
+        if users.is_empty() {
+            anyhow::bail!("No members of {} are visible to this token", cli.org_members.as_deref().unwrap_or("the organization"));
+        }
+        eprintln!("{}", format!("Fetching contributions for {} users...", users.len()).bright_black());
+        let entries = fetch_leaderboard(&client, &users).await;
+        display_leaderboard(&entries);
+        return Ok(());
+    }
+
+    if cli.peek {
+        let options = calendar_only_options(&cli, window, timezone);
+        let client = GitHubClient::new(cli.username.clone().unwrap_or_default(), cli.token, options)
+            .context("Failed to create GitHub client")?;
+        let cache_key = client.today_count_cache_key();
+        let cached = if cli.no_cache { None } else { cache::load_stats::<u32>(&cache_key, PEEK_CACHE_TTL) };
+        let count = match cached {
+            Some(count) => count,
+            None => {
+                let count = client.get_today_count().await?;
+                cache::store_stats(&cache_key, &count).ok();
+                count
+            }
+        };
+        println!("{}", count);
+        return Ok(());
+    }
 
     // Get username from args or from authenticated user
-    let username = if let Some(username) = cli.username {
+    let username = if let Some(username) = cli.username.clone().or_else(|| cli.org.clone()) {
         username
     } else {
         // Try to get current authenticated user
         let output = std::process::Command::new("gh")
-            .args(&["api", "user", "--jq", ".login"])
+            .args(["api", "user", "--jq", ".login"])
             .output();
         
         match output {
@@ -678,67 +5567,435 @@ async fn main() -> Result<()> {
         }
     };
 
-    let client = GitHubClient::new(username, cli.token)
+    let theme = match &cli.theme_file {
+        Some(path) => load_theme_file(path).unwrap_or_else(|e| {
+            eprintln!("{}", format!("{:#}, using the {} theme", e, cli.theme).bright_yellow());
+            find_theme(&cli.theme).unwrap_or(&THEMES[0])
+        }),
+        None => find_theme(&cli.theme)?,
+    };
+    validate_quantiles(&cli.quantiles)?;
+
+    let repo_filter = cli.repo_filter.as_deref()
+        .map(regex::Regex::new)
+        .transpose()
+        .context("Invalid --repo-filter pattern")?;
+
+    let graphql_extra = match &cli.graphql_extra {
+        Some(path) => {
+            let fields = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            validate_graphql_fragment(&fields)?;
+            Some(GraphqlExtra {
+                fields,
+                output: cli.graphql_extra_out.clone().unwrap_or_else(|| DEFAULT_GRAPHQL_EXTRA_OUTPUT.into()),
+            })
+        }
+        None => None,
+    };
+
+    // Resolve the renderer up front so an unknown --format fails before any API calls
+    let render_context = RenderContext {
+        theme,
+        svg_responsive: cli.svg_responsive,
+        to_file: cli.output.is_some(),
+        display: DisplayOptions {
+            show_counts: cli.show_counts,
+            ascii,
+            spacing: cli.spacing,
+            accent_today: cli.accent_today,
+            legend_stats: cli.legend_stats,
+            no_footer: cli.no_footer,
+            highlight_streak: cli.highlight_streak,
+            month_separators: cli.month_separators,
+            show_url: cli.show_url,
+            hyperlinks: cli.output.is_none() && terminal_supports_hyperlinks(),
+            active_threshold: cli.active_threshold,
+            weekdays_only: cli.weekdays_only,
+            week_start: cli.week_start,
+            year_goal: cli.year_goal,
+            palette: Palette {
+                levels: theme.levels,
+                depth: if no_color { ColorDepth::None } else { cli.color_depth.unwrap_or_else(ColorDepth::detect) },
+                // Custom symbols are most likely emoji, which ASCII mode is there to avoid
+                symbols: theme.symbols.filter(|_| !ascii),
+                legend: theme.legend,
+            },
+            timezone,
+        },
+    };
+    if render_context.display.palette.depth == ColorDepth::None {
+        colored::control::set_override(false);
+    }
+
+    if let Some(other) = &cli.diff_graph {
+        let options = calendar_only_options(&cli, window, timezone);
+        let client = GitHubClient::new(String::new(), cli.token, options)
+            .context("Failed to create GitHub client")?;
+        let mut results = fetch_users_stats(&client, &[username, other.clone()]).await.into_iter();
+        let mut next_stats = || -> Result<Stats> {
+            let (username, result) = results.next().context("Missing stats for --diff-graph")?;
+            result.with_context(|| format!("Failed to fetch stats for {}", username))
+        };
+        let (first, second) = (next_stats()?, next_stats()?);
+        let display = DisplayOptions { show_counts: false, ..render_context.display };
+        display_diff_graph(&first, &second, &display, &mut stdout().lock())?;
+        return Ok(());
+    }
+    let format = resolve_format(cli.format.as_deref(), cli.output.as_deref())?;
+    if cli.watch.is_some() && format != "text" {
+        anyhow::bail!("--watch redraws the text graph, so it can't be combined with --format {}", format);
+    }
+    let renderer = find_renderer(&format, &render_context)?;
+
+    let options = ClientOptions {
+        max_requests: cli.max_requests,
+        retry_budget: cli.max_total_retry_time,
+        timeout: Duration::from_secs(cli.timeout),
+        endpoints: cli_endpoints(&cli),
+        gh_fallback: !cli.no_gh_fallback,
+        max_pages: cli.max_pages,
+        repo_count: cli.repos as usize,
+        scan_repos: cli.scan_repos.unwrap_or(DEFAULT_SCAN_REPOS).min(cli.repos as usize),
+        public_only: cli.public_only,
+        window,
+        verbose: cli.verbose,
+        graphql_extra,
+        pinned: cli.pinned,
+        list_commits: cli.list_commits,
+        punchcard: format == "punchcard",
+        all_authors: cli.org.is_some(),
+        dedupe_forks: cli.dedupe_forks,
+        week_start: cli.week_start.unwrap_or(DEFAULT_PERIOD_WEEK_START),
+        timezone,
+        strict: cli.strict,
+        repo_filter,
+        language: cli.lang.clone(),
+        source: cli.source,
+    };
+    if cli.also_user.len() != cli.also_token.len() {
+        anyhow::bail!("Every --also-user needs its own --also-token ({} users, {} tokens)", cli.also_user.len(), cli.also_token.len());
+    }
+    let also_accounts: Vec<(String, String)> = cli.also_user.iter().cloned().zip(cli.also_token.iter().cloned()).collect();
+    let also_options = calendar_only_options(&cli, window, timezone);
+    // --cache-ttl 0 turns reading the cache off, like --no-cache
+    let cache_ttl = (!cli.no_cache && cli.cache_ttl > 0).then(|| Duration::from_secs(cli.cache_ttl * 60));
+
+    let client = GitHubClient::new(username, cli.token, options)
         .context("Failed to create GitHub client")?;
 
-    // Start loading animation in background
-    let loading_handle = tokio::spawn(show_loading_animation());
+    if cli.org.is_some() {
+        if format != "text" && format != "json" {
+            anyhow::bail!("--org prints a table or --format json, not --format {}", format);
+        }
+        if !client.authenticated {
+            anyhow::bail!("--org lists repositories through GraphQL, which needs a token");
+        }
+        let mut repos = client.get_org_repos().await?;
+        sort_repos(&mut repos, cli.sort);
+        match format.as_str() {
+            "json" => println!("{}", serde_json::to_string_pretty(&repos)?),
+            _ => display_org_repos(&client.username, repos, &render_context.display)?,
+        }
+        return Ok(());
+    }
+
+    if let Some(count) = cli.top_repos {
+        if format != "text" && format != "json" {
+            anyhow::bail!("--top-repos prints a table or --format json, not --format {}", format);
+        }
+        let repos = client.get_top_repos(count).await?;
+        match format.as_str() {
+            "json" => println!("{}", serde_json::to_string_pretty(&repos)?),
+            _ => display_top_repos(&client.username, &repos),
+        }
+        return Ok(());
+    }
+
+    let cache_key = client.stats_cache_key(cli.repo.as_deref(), &cli.quantiles);
+
+    if let Some(Command::WarmCache) = cli.command {
+        let stats = client.fetch_stats(cli.repo.as_deref(), &cli.quantiles).await?;
+        cache::store_stats(&cache_key, &stats).context("Failed to write the stats cache")?;
+        return Ok(());
+    }
+
+    // --graphql-extra writes its side file while fetching, so it always needs a real fetch,
+    // and a benchmark measures the requests a cached run wouldn't make
+    let cached: Option<Stats> = if cli.graphql_extra.is_some() || cli.benchmark {
+        None
+    } else {
+        cache_ttl.and_then(|ttl| cache::load_stats(&cache_key, ttl))
+    };
+
+    // Only animate for the interactive text view so piped formats and files stay clean
+    let interactive = format == "text" && cli.output.is_none() && !cli.benchmark;
+    let loading = (interactive && cached.is_none()).then(|| {
+        let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel();
+        // A past window's placeholder should have its months, not the last year's
+        let today = today_in(timezone);
+        let last_day = window.map_or(today, |window| window.to.min(today));
+        (shutdown, tokio::spawn(show_loading_animation(shutdown_rx, render_context.display, last_day)))
+    });
     
     // Fetch stats
-    let stats_result = client.get_stats().await;
+    let fetch_started = std::time::Instant::now();
+    let stats_result = match cached {
+        Some(stats) => Ok(stats),
+        None => {
+            let result = client.fetch_stats(cli.repo.as_deref(), &cli.quantiles).await;
+            if let Ok(stats) = &result {
+                // A read-only or missing cache directory only costs the next run a fetch
+                cache::store_stats(&cache_key, stats).ok();
+            }
+            result
+        }
+    };
+    let stats_result = match stats_result {
+        Ok(stats) if !also_accounts.is_empty() => merge_accounts(stats, &also_accounts, &also_options, cache_ttl, &cli.quantiles).await,
+        other => other,
+    };
     
-    // Stop loading animation and clear screen
-    loading_handle.abort();
-    print!("\x1b[2J\x1b[1;1H"); // Clear entire screen and move cursor to top-left
-    stdout().flush().unwrap();
+    let fetch_time = fetch_started.elapsed();
+    
+    // Ask the loading animation to stop and wait until it has erased itself
+    if let Some((shutdown, loading_handle)) = loading {
+        shutdown.send(()).ok();
+        loading_handle.await.ok();
+    }
 
     match stats_result {
-        Ok(stats) => {
-            match cli.format.as_str() {
-                "json" => {
-                    println!("{}", serde_json::to_string_pretty(&stats)?);
-                }
-                _ => {
-                    display_contribution_graph(&stats);
-                    
-                    // Enable raw mode for key detection
-                    terminal::enable_raw_mode()?;
-                    
-                    println!();
-                    println!("{}", "Press 'q' or Ctrl+C to exit".bright_black());
-                    
-                    // Keep the process running and listen for key presses
-                    loop {
-                        if event::poll(Duration::from_millis(100))? {
-                            if let CrosstermEvent::Key(key_event) = event::read()? {
-                                if key_event.kind == KeyEventKind::Press {
-                                    match key_event.code {
-                                        KeyCode::Char('q') | KeyCode::Char('Q') => {
-                                            break;
-                                        }
-                                        KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
-                                            break;
-                                        }
-                                        KeyCode::Esc => {
-                                            break;
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    
-                    // Disable raw mode before exiting
-                    terminal::disable_raw_mode()?;
+        Ok(mut stats) => {
+            let render_started = std::time::Instant::now();
+            prepare_stats(&mut stats, cli.normalize, cli.sort, cli.weekdays_only, timezone);
+            if cli.include_private && !stats.viewer_is_user {
+                eprintln!("{}", format!("--include-private only applies when the token belongs to {}; the calendar has what it can see", stats.username).bright_yellow());
+            }
+
+            if let Some(path) = &cli.summary_json {
+                let summary = compute_summary(&stats.contribution_graph, cli.active_threshold, cli.weekdays_only, render_context.display.period_week_start(), timezone);
+                std::fs::write(path, serde_json::to_string_pretty(&summary)?)
+                    .with_context(|| format!("Failed to write summary to {}", path.display()))?;
+            }
+
+            if let Some(path) = &cli.output {
+                // Escape codes would only clutter a saved text graph (ansi-text keeps them on purpose)
+                colored::control::set_override(false);
+                let mut file = std::fs::File::create(path)
+                    .with_context(|| format!("Failed to create {}", path.display()))?;
+                renderer.render(&stats, &mut file)?;
+                restore_color_choice(render_context.display.palette.depth);
+            } else {
+                renderer.render(&stats, &mut stdout())?;
+                if interactive {
+                    let watch = cli.watch.map(|secs| Watch {
+                        every: Duration::from_secs(secs),
+                        client: &client,
+                        repo: cli.repo.as_deref(),
+                        quantiles: &cli.quantiles,
+                        cache_key: &cache_key,
+                        also_accounts: &also_accounts,
+                        also_options: &also_options,
+                        normalize: cli.normalize,
+                        sort: cli.sort,
+                        weekdays_only: cli.weekdays_only,
+                    });
+                    run_interactive(stats, &render_context.display, watch.as_ref()).await?;
                 }
             }
+            if cli.benchmark {
+                print_benchmark(&client.metrics(), fetch_time, render_started.elapsed(), started.elapsed());
+            }
         }
         Err(e) => {
-            eprintln!("{} {}", "❌ Error:".bright_red(), e);
+            eprintln!("{} {:#}", glyphs(render_context.display.ascii).error_prefix.bright_red(), e);
+            if cli.benchmark {
+                print_benchmark(&client.metrics(), fetch_time, Duration::ZERO, started.elapsed());
+            }
             std::process::exit(1);
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn unparseable_commit_dates_are_counted_as_skipped() {
+        let commits = vec![
+            serde_json::json!({ "sha": "good", "commit": { "author": { "date": "2024-05-15T10:00:00Z" } } }),
+            serde_json::json!({ "sha": "bad", "commit": { "author": { "date": "yesterday-ish" } } }),
+        ];
+        let mut skipped = Vec::new();
+        let counts = count_commits_by_period(
+            &commits,
+            utc("2024-05-15T00:00:00Z"),
+            utc("2024-05-15T23:59:59Z"),
+            utc("2024-05-13T00:00:00Z"),
+            |sha, date| skipped.push((sha.to_string(), date.to_string())),
+        );
+
+        assert_eq!(counts.month, 2);
+        assert_eq!(counts.week, 1);
+        assert_eq!(counts.today, 1);
+        assert_eq!(counts.unparseable_dates, 1);
+        assert_eq!(skipped, vec![("bad".to_string(), "yesterday-ish".to_string())]);
+    }
+
+    #[test]
+    fn missing_user_lookup_is_account_not_found() {
+        let error = check_user_response("ghost", reqwest::StatusCode::NOT_FOUND).unwrap_err();
+        assert!(error.is::<AccountNotFound>());
+        assert!(check_user_response("octocat", reqwest::StatusCode::OK).is_ok());
+    }
+
+    #[test]
+    fn null_graphql_user_is_account_not_found() {
+        let response: GraphQLResponse = serde_json::from_value(serde_json::json!({
+            "data": { "user": null, "viewer": null }
+        })).unwrap();
+        let error = require_user("ghost", response.data.user).unwrap_err();
+        assert_eq!(error.downcast_ref::<AccountNotFound>().map(|e| e.username.as_str()), Some("ghost"));
+    }
+
+    fn naive(timestamp: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn time_in_spring_forward_gap_moves_out_of_it() {
+        // Berlin skips from 02:00 to 03:00 on 2024-03-31
+        let zone = chrono_tz::Europe::Berlin;
+        assert_eq!(resolve_local_time(&zone, naive("2024-03-31 02:30"), false), utc("2024-03-31T01:00:00Z"));
+        assert_eq!(resolve_local_time(&zone, naive("2024-03-31 02:30"), true), utc("2024-03-31T00:45:00Z"));
+    }
+
+    #[test]
+    fn time_in_fall_back_overlap_picks_an_occurrence() {
+        // Berlin repeats 02:00 to 03:00 on 2024-10-27, first in CEST, then in CET
+        let zone = chrono_tz::Europe::Berlin;
+        assert_eq!(resolve_local_time(&zone, naive("2024-10-27 02:30"), false), utc("2024-10-27T00:30:00Z"));
+        assert_eq!(resolve_local_time(&zone, naive("2024-10-27 02:30"), true), utc("2024-10-27T01:30:00Z"));
+    }
+
+    /// `weeks` full weeks from `start`, one contribution a day
+    fn daily_graph(start: NaiveDate, weeks: i64) -> ContributionGraph {
+        let weeks = (0..weeks)
+            .map(|week| Week {
+                days: (0..7)
+                    .map(|day| Day {
+                        date: (start + chrono::Duration::days(week * 7 + day)).format("%Y-%m-%d").to_string(),
+                        count: 1,
+                        level: 1,
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        let total_contributions = weeks.len() as u32 * 7;
+        ContributionGraph { weeks, total_contributions }
+    }
+
+    fn stats_for(username: &str, contribution_graph: ContributionGraph) -> Stats {
+        Stats {
+            username: username.to_string(),
+            viewer_is_user: false,
+            private_excluded: false,
+            limited_history: false,
+            contribution_graph,
+            recent_repos: Vec::new(),
+            pinned_repos: Vec::new(),
+            breakdown: None,
+            most_active_weekday: None,
+            punchcard: None,
+        }
+    }
+
+    /// Row labels and the cell tooltips of each row of `render_html`'s table, below the month header
+    fn html_rows(html: &str) -> (Vec<String>, Vec<Vec<Option<String>>>) {
+        let document = roxmltree::Document::parse(html).unwrap();
+        document.descendants()
+            .filter(|node| node.has_tag_name("tr"))
+            .skip(1)
+            .map(|row| {
+                let label = row.children().find(|cell| cell.has_tag_name("th")).and_then(|cell| cell.text()).unwrap_or_default();
+                let cells = row.children()
+                    .filter(|cell| cell.has_tag_name("td"))
+                    .map(|cell| cell.attribute("title").map(str::to_string))
+                    .collect();
+                (label.to_string(), cells)
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn window_edges_mid_week_trim_partial_weeks() {
+        // Four Sunday-to-Saturday weeks from 2024-05-05
+        let mut graph = daily_graph(NaiveDate::from_ymd_opt(2024, 5, 5).unwrap(), 4);
+        let window = DateWindow {
+            from: NaiveDate::from_ymd_opt(2024, 5, 8).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 5, 22).unwrap(),
+        };
+
+        assert!(graph.retain_window(window));
+        let days: Vec<usize> = graph.weeks.iter().map(|week| week.days.len()).collect();
+        assert_eq!(days, vec![4, 7, 4]);
+        assert_eq!(graph.weeks[0].days[0].date, "2024-05-08");
+        assert_eq!(graph.weeks[2].days[3].date, "2024-05-22");
+        assert_eq!(graph.total_contributions, 15);
+        assert!(!graph.retain_window(window));
+    }
+
+    #[test]
+    fn html_puts_a_partial_first_week_on_its_weekday_rows() {
+        let mut graph = daily_graph(NaiveDate::from_ymd_opt(2024, 5, 5).unwrap(), 3);
+        graph.retain_window(DateWindow {
+            from: NaiveDate::from_ymd_opt(2024, 5, 8).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 5, 25).unwrap(),
+        });
+        let (labels, rows) = html_rows(&render_html(&stats_for("octocat", graph), &THEMES[0], WeekStart::Sunday));
+
+        assert_eq!(labels, ["", "Mon", "", "Wed", "", "Fri", ""]);
+        for row in &rows[..3] {
+            assert_eq!(row[0], None);
+        }
+        assert_eq!(rows[3][0].as_deref(), Some("2024-05-08: 1 contributions"));
+        assert_eq!(rows[6][0].as_deref(), Some("2024-05-11: 1 contributions"));
+        assert_eq!(rows[0][1].as_deref(), Some("2024-05-12: 1 contributions"));
+    }
+
+    #[test]
+    fn html_rows_follow_week_start() {
+        let mut graph = daily_graph(NaiveDate::from_ymd_opt(2024, 5, 5).unwrap(), 3);
+        graph.retain_window(DateWindow {
+            from: NaiveDate::from_ymd_opt(2024, 5, 8).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 5, 25).unwrap(),
+        });
+        let (labels, rows) = html_rows(&render_html(&stats_for("octocat", graph), &THEMES[0], WeekStart::Monday));
+
+        assert_eq!(labels, ["Mon", "", "Wed", "", "Fri", "", ""]);
+        assert_eq!(rows[0][0], None);
+        assert_eq!(rows[2][0].as_deref(), Some("2024-05-08: 1 contributions"));
+        // Sunday the 12th closes the first Monday week instead of opening the next
+        assert_eq!(rows[6][0].as_deref(), Some("2024-05-12: 1 contributions"));
+        assert_eq!(rows[0][1].as_deref(), Some("2024-05-13: 1 contributions"));
+    }
+
+    #[test]
+    fn svg_is_well_formed() {
+        let stats = stats_for("<o'ctocat & \"friends\">", daily_graph(NaiveDate::from_ymd_opt(2024, 1, 7).unwrap(), 8));
+        for responsive in [false, true] {
+            let svg = render_svg(&stats, &THEMES[0], responsive, WeekStart::Sunday).unwrap();
+            let document = roxmltree::Document::parse(&svg)
+                .unwrap_or_else(|e| panic!("responsive={} SVG doesn't parse: {}", responsive, e));
+            assert_eq!(document.root_element().tag_name().name(), "svg");
+            assert!(document.descendants().any(|node| node.text().is_some_and(|text| text.contains(&stats.username))));
+        }
+    }
+}